@@ -0,0 +1,227 @@
+use std::collections::{HashMap, VecDeque};
+use std::f32;
+use std::time::{Duration, Instant};
+use ai::{actions, Action, Ai, Evaluation};
+use battle::{Battle, Command};
+use game_object::*;
+
+const MAX_TABLE_ENTRIES: usize = 1 << 18;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AlphaBetaConfig {
+    /// Plies to search, where a ply is one unit's action or the end of a turn.
+    pub max_depth: u32,
+    /// Only the best actions by static evaluation are searched at each node.
+    pub max_branching: usize,
+    /// Positions searched before deepening stops, keeping the best action
+    /// from the last depth finished.
+    pub max_nodes: u64,
+    /// Stops deepening early; see `Timing` for when that's wanted.
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for AlphaBetaConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_branching: 12,
+            max_nodes: 5_000,
+            time_budget: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct TableEntry {
+    depth: u32,
+    value: f32,
+    bound: Bound,
+    best: Option<Action>,
+}
+
+/// Depth limited minimax with alpha-beta pruning, iterative deepening and a
+/// transposition table keyed by `Battle::state_hash`.
+pub struct AlphaBetaAi {
+    config: AlphaBetaConfig,
+    evaluation: Box<dyn Evaluation>,
+    table: HashMap<u64, TableEntry>,
+    plan: VecDeque<Command>,
+    planned_turn: Option<(Side, u32)>,
+    root_side: Side,
+    deadline: Option<Instant>,
+    nodes: u64,
+    units: Option<Vec<UnitId>>,
+}
+
+impl AlphaBetaAi {
+    pub fn new(config: AlphaBetaConfig, evaluation: Box<dyn Evaluation>) -> Self {
+        Self {
+            config,
+            evaluation,
+            table: HashMap::new(),
+            plan: VecDeque::new(),
+            planned_turn: None,
+            root_side: Side::Enemy,
+            deadline: None,
+            nodes: 0,
+            units: None,
+        }
+    }
+
+    /// Only lets the search move the given units on its own side.
+    pub fn restrict_to(&mut self, units: Vec<UnitId>) {
+        self.units = Some(units);
+    }
+
+    pub fn allows(&self, battle: &Battle, action: &Action) -> bool {
+        match (action, &self.units) {
+            (&Action::Unit { unit, .. }, &Some(ref units)) if battle.current_turn() == self.root_side => {
+                units.contains(&unit)
+            }
+            _ => true,
+        }
+    }
+
+    pub fn best_action(&mut self, battle: &Battle) -> Action {
+        self.root_side = battle.current_turn();
+        self.deadline = self.config.time_budget.map(|budget| Instant::now() + budget);
+        self.nodes = 0;
+        self.table.clear();
+        let mut best = self.ordered_actions(battle, None).into_iter().next().unwrap_or(Action::EndTurn);
+        for depth in 1..self.config.max_depth + 1 {
+            if self.search(battle, depth, -f32::INFINITY, f32::INFINITY).is_none() {
+                break;
+            }
+            if let Some(action) = self.table.get(&battle.state_hash()).and_then(|entry| entry.best) {
+                best = action;
+            }
+        }
+        best
+    }
+
+    fn out_of_budget(&self) -> bool {
+        self.nodes >= self.config.max_nodes || self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+    }
+
+    /// Actions sorted best first for the side to move, trimmed to the branching limit.
+    fn ordered_actions(&self, battle: &Battle, table_best: Option<Action>) -> Vec<Action> {
+        let mover = battle.current_turn();
+        let mut scored: Vec<(f32, Action)> = actions(battle)
+            .into_iter()
+            .filter(|action| self.allows(battle, action))
+            .filter_map(|action| {
+                let mut child = battle.clone();
+                action.apply(&mut child).ok()?;
+                let score = if Some(action) == table_best {
+                    f32::INFINITY
+                } else {
+                    self.evaluation.evaluate(&child, mover)
+                };
+                Some((score, action))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(::std::cmp::Ordering::Equal));
+        scored.truncate(self.config.max_branching);
+        scored.into_iter().map(|(_, action)| action).collect()
+    }
+
+    /// Minimax value of `battle` for the root side, or `None` if the node or
+    /// time budget ran out.
+    fn search(&mut self, battle: &Battle, depth: u32, mut alpha: f32, mut beta: f32) -> Option<f32> {
+        if self.out_of_budget() {
+            return None;
+        }
+        self.nodes += 1;
+        if depth == 0 || battle.is_over() {
+            return Some(self.evaluation.evaluate(battle, self.root_side));
+        }
+        let key = battle.state_hash();
+        let mut table_best = None;
+        if let Some(entry) = self.table.get(&key) {
+            table_best = entry.best;
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return Some(entry.value),
+                    Bound::Lower => alpha = alpha.max(entry.value),
+                    Bound::Upper => beta = beta.min(entry.value),
+                }
+                if alpha >= beta {
+                    return Some(entry.value);
+                }
+            }
+        }
+
+        let maximizing = battle.current_turn() == self.root_side;
+        let (original_alpha, original_beta) = (alpha, beta);
+        let mut best_value = if maximizing { -f32::INFINITY } else { f32::INFINITY };
+        let mut best_action = None;
+        for action in self.ordered_actions(battle, table_best) {
+            let mut child = battle.clone();
+            if action.apply(&mut child).is_err() {
+                continue;
+            }
+            let value = self.search(&child, depth - 1, alpha, beta)?;
+            if maximizing {
+                if value > best_value {
+                    best_value = value;
+                    best_action = Some(action);
+                }
+                alpha = alpha.max(value);
+            } else {
+                if value < best_value {
+                    best_value = value;
+                    best_action = Some(action);
+                }
+                beta = beta.min(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_value <= original_alpha {
+            Bound::Upper
+        } else if best_value >= original_beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        if self.table.len() < MAX_TABLE_ENTRIES {
+            self.table.insert(
+                key,
+                TableEntry {
+                    depth,
+                    value: best_value,
+                    bound,
+                    best: best_action,
+                },
+            );
+        }
+        Some(best_value)
+    }
+}
+
+impl Ai for AlphaBetaAi {
+    fn name(&self) -> String {
+        "alphabeta".to_owned()
+    }
+
+    fn next_command(&mut self, battle: &Battle) -> Command {
+        let turn = (battle.current_turn(), battle.turn_number());
+        if self.planned_turn != Some(turn) {
+            self.plan.clear();
+            self.planned_turn = Some(turn);
+        }
+        if self.plan.is_empty() {
+            let action = self.best_action(battle);
+            self.plan.extend(action.commands());
+        }
+        self.plan.pop_front().unwrap_or(Command::EndTurn)
+    }
+}
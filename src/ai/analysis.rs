@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use battle::Battle;
+use game_object::*;
+
+/// One value per tile of a battle's grid.
+#[derive(Clone, Debug)]
+pub struct TileMap<T> {
+    width: i32,
+    height: i32,
+    values: Vec<T>,
+}
+
+impl<T: Copy + Default> TileMap<T> {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![T::default(); (width.max(0) * height.max(0)) as usize],
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn index(&self, tile: Tile) -> Option<usize> {
+        if tile.x() < 0 || tile.y() < 0 || tile.x() >= self.width || tile.y() >= self.height {
+            return None;
+        }
+        Some((tile.y() * self.width + tile.x()) as usize)
+    }
+
+    /// The value at `tile`, or the default outside the map.
+    pub fn get(&self, tile: Tile) -> T {
+        self.index(tile).map(|index| self.values[index]).unwrap_or_default()
+    }
+
+    pub fn get_mut(&mut self, tile: Tile) -> Option<&mut T> {
+        match self.index(tile) {
+            Some(index) => Some(&mut self.values[index]),
+            None => None,
+        }
+    }
+
+    pub fn tiles(&self) -> Vec<Tile> {
+        let mut tiles = Vec::with_capacity(self.values.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                tiles.push(Tile::new(x as i16, y as i16));
+            }
+        }
+        tiles
+    }
+}
+
+impl<T: Copy + Default + PartialOrd> TileMap<T> {
+    pub fn max(&self) -> T {
+        self.values
+            .iter()
+            .fold(T::default(), |max, value| if *value > max { *value } else { max })
+    }
+}
+
+/// Damage `side` could deal to each tile next turn, summed over every unit
+/// that could move somewhere it reaches the tile from.
+pub fn threat_map(battle: &Battle, side: Side) -> TileMap<u32> {
+    let mut map = TileMap::new(battle.width(), battle.height());
+    for unit_id in battle.units().ids_of(side) {
+        let unit = match battle.units().get_unit(unit_id) {
+            Some(unit) => unit,
+            None => continue,
+        };
+        let mut origins = battle.reachable_tiles(unit_id);
+        origins.insert(unit.get_tile());
+        let mut threatened = HashSet::new();
+        for origin in origins {
+            threatened.extend(battle.attack_tiles_from(unit_id, origin));
+        }
+        for tile in threatened {
+            if let Some(value) = map.get_mut(tile) {
+                *value += unit.get_damage() as u32;
+            }
+        }
+    }
+    map
+}
+
+/// How strongly each side holds every tile: the health of each unit falling
+/// off with distance, counted positive for `side` and negative for its
+/// opponent.
+pub fn influence_map(battle: &Battle, side: Side) -> TileMap<f32> {
+    let mut map: TileMap<f32> = TileMap::new(battle.width(), battle.height());
+    for tile in map.tiles() {
+        let mut influence = 0.0;
+        for unit in battle.units().iter() {
+            let strength = unit.get_health().get_current().max(0) as f32;
+            let falloff = strength / (1.0 + unit.get_tile().distance(tile) as f32);
+            if unit.get_side() == side {
+                influence += falloff;
+            } else {
+                influence -= falloff;
+            }
+        }
+        if let Some(value) = map.get_mut(tile) {
+            *value = influence;
+        }
+    }
+    map
+}
+
+/// Threat and influence maps for both sides of one position.
+pub struct Analysis {
+    player_threat: TileMap<u32>,
+    enemy_threat: TileMap<u32>,
+    influence: TileMap<f32>,
+}
+
+impl Analysis {
+    pub fn new(battle: &Battle) -> Self {
+        Self {
+            player_threat: threat_map(battle, Side::Player),
+            enemy_threat: threat_map(battle, Side::Enemy),
+            influence: influence_map(battle, Side::Player),
+        }
+    }
+
+    pub fn threat(&self, side: Side) -> &TileMap<u32> {
+        match side {
+            Side::Player => &self.player_threat,
+            Side::Enemy => &self.enemy_threat,
+        }
+    }
+
+    /// Damage a unit of `side` standing on `tile` could take next turn.
+    pub fn danger(&self, side: Side, tile: Tile) -> u32 {
+        self.threat(side.opponent()).get(tile)
+    }
+
+    /// Influence map with positive values where the player is stronger.
+    pub fn influence(&self) -> &TileMap<f32> {
+        &self.influence
+    }
+
+    pub fn influence_for(&self, side: Side, tile: Tile) -> f32 {
+        match side {
+            Side::Player => self.influence.get(tile),
+            Side::Enemy => -self.influence.get(tile),
+        }
+    }
+}
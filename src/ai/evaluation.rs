@@ -0,0 +1,111 @@
+use ai::Analysis;
+use battle::Battle;
+use game_object::*;
+
+pub const WIN_SCORE: f32 = 10_000.0;
+
+pub trait Evaluation {
+    /// Scores the position from `side`'s point of view; higher is better.
+    fn evaluate(&self, battle: &Battle, side: Side) -> f32;
+}
+
+/// Scores a side by its remaining health, unit count, closeness to the
+/// enemy and how much damage it could deal next turn. Kings and healers can
+/// be weighted on top of that. The `danger` and `influence` terms read the
+/// threat and influence maps, which cost a pathfinding pass per unit, so
+/// the maps are only built when one of them is weighted. Neither is by
+/// default; personalities that care turn them on.
+#[derive(Clone, Copy, Debug)]
+pub struct WeightedEvaluation {
+    pub health: f32,
+    pub units: f32,
+    pub position: f32,
+    pub threat: f32,
+    pub king: f32,
+    pub healers: f32,
+    /// Per point of damage a unit could take next turn, up to its health.
+    pub danger: f32,
+    /// Per point of influence its side holds on a unit's tile.
+    pub influence: f32,
+}
+
+impl Default for WeightedEvaluation {
+    fn default() -> Self {
+        Self {
+            health: 1.0,
+            units: 5.0,
+            position: 0.1,
+            threat: 0.5,
+            king: 0.0,
+            healers: 0.0,
+            danger: 0.0,
+            influence: 0.0,
+        }
+    }
+}
+
+impl WeightedEvaluation {
+    fn side_score(&self, battle: &Battle, analysis: Option<&Analysis>, side: Side) -> f32 {
+        let units = battle.units();
+        let enemies: Vec<&Unit> = units
+            .ids_of(side.opponent())
+            .into_iter()
+            .filter_map(|id| units.get_unit(id))
+            .collect();
+        let mut score = 0.0;
+        for unit in units.ids_of(side).into_iter().filter_map(|id| units.get_unit(id)) {
+            let health = unit.get_health().get_current().max(0) as f32;
+            score += self.units;
+            score += self.health * health;
+            if unit.get_class() == UnitClass::King {
+                score += self.king * health;
+            }
+            if unit.is_healer() {
+                score += self.healers * health;
+            }
+            if let Some(analysis) = analysis {
+                let danger = (analysis.danger(side, unit.get_tile()) as f32).min(health);
+                score -= self.danger * danger;
+                score += self.influence * analysis.influence_for(side, unit.get_tile());
+            }
+            let nearest = enemies
+                .iter()
+                .map(|enemy| unit.get_tile().distance(enemy.get_tile()))
+                .min();
+            if let Some(distance) = nearest {
+                score -= self.position * distance as f32;
+                if distance <= unit.get_movement() as u32 + reach(unit) {
+                    score += self.threat * unit.get_damage() as f32;
+                }
+            }
+        }
+        score
+    }
+}
+
+impl Evaluation for WeightedEvaluation {
+    fn evaluate(&self, battle: &Battle, side: Side) -> f32 {
+        match battle.winner() {
+            Some(winner) if winner == side => WIN_SCORE,
+            Some(_) => -WIN_SCORE,
+            None => {
+                let analysis = if self.danger != 0.0 || self.influence != 0.0 {
+                    Some(Analysis::new(battle))
+                } else {
+                    None
+                };
+                let analysis = analysis.as_ref();
+                self.side_score(battle, analysis, side) - self.side_score(battle, analysis, side.opponent())
+            }
+        }
+    }
+}
+
+/// The furthest tile, in steps, the unit's attack pattern covers.
+pub fn reach(unit: &Unit) -> u32 {
+    unit.get_attack_pattern()
+        .iter()
+        .map(|offset| (offset.x().abs() + offset.y().abs()) as u32)
+        .max()
+        .unwrap_or(0)
+}
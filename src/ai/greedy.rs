@@ -0,0 +1,78 @@
+use ai::analysis::threat_map;
+use ai::{shuffled, Ai};
+use battle::{rng_from_seed, Battle, Command};
+use game_object::*;
+use rand::XorShiftRng;
+
+/// Attacks the weakest enemy in range, otherwise walks towards the closest
+/// tile from which it could attack next turn, preferring the least
+/// threatened one.
+pub struct GreedyAi {
+    rng: XorShiftRng,
+}
+
+impl GreedyAi {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: rng_from_seed(seed),
+        }
+    }
+
+    fn best_attack(&self, battle: &Battle, unit_id: UnitId) -> Option<Command> {
+        battle
+            .attack_targets(unit_id)
+            .into_iter()
+            .min_by_key(|target| {
+                battle.units().get_unit(*target).map(|unit| unit.get_health().get_current())
+            })
+            .map(|target| Command::Attack { unit: unit_id, target })
+    }
+
+    fn best_move(&mut self, battle: &Battle, unit_id: UnitId) -> Option<Command> {
+        let unit = battle.units().get_unit(unit_id)?;
+        let enemies: Vec<Tile> = battle
+            .units()
+            .ids_of(unit.get_side().opponent())
+            .into_iter()
+            .filter_map(|id| battle.units().get_unit(id).map(|enemy| enemy.get_tile()))
+            .collect();
+        let danger = threat_map(battle, unit.get_side().opponent());
+        let score = |tile: Tile| {
+            let threatens = battle
+                .attack_tiles_from(unit_id, tile)
+                .into_iter()
+                .any(|target| enemies.contains(&target));
+            let distance = enemies.iter().map(|enemy| tile.distance(*enemy)).min().unwrap_or(0);
+            (!threatens, distance, danger.get(tile))
+        };
+        let mut tiles: Vec<Tile> = battle.reachable_tiles(unit_id).into_iter().collect();
+        tiles.sort_by_key(|tile| (tile.x(), tile.y()));
+        let best = shuffled(&mut self.rng, tiles).into_iter().min_by_key(|tile| score(*tile))?;
+        if score(best) < score(unit.get_tile()) {
+            Some(Command::Move { unit: unit_id, to: best })
+        } else {
+            None
+        }
+    }
+}
+
+impl Ai for GreedyAi {
+    fn name(&self) -> String {
+        "greedy".to_owned()
+    }
+
+    fn next_command(&mut self, battle: &Battle) -> Command {
+        let unit_ids = battle.units().ids_of(battle.current_turn());
+        for unit_id in unit_ids.iter().filter(|id| battle.can_attack(**id)) {
+            if let Some(command) = self.best_attack(battle, *unit_id) {
+                return command;
+            }
+        }
+        for unit_id in unit_ids.iter().filter(|id| battle.can_move(**id)) {
+            if let Some(command) = self.best_move(battle, *unit_id) {
+                return command;
+            }
+        }
+        Command::EndTurn
+    }
+}
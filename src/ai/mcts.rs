@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+use ai::{actions, play_turn, shuffled, Action, Ai, Evaluation, GreedyAi, WeightedEvaluation};
+use battle::{rng_from_seed, Battle, Command};
+use game_object::*;
+use rand::{Rng, XorShiftRng};
+
+#[derive(Clone, Copy, Debug)]
+pub struct MctsConfig {
+    /// Iterations per thread; the search also stops when the time budget runs out.
+    pub iterations: u32,
+    /// Lets a search stop short of `iterations`, for slow machines.
+    pub time_budget: Option<Duration>,
+    pub exploration: f32,
+    /// Whole turns the default policy plays before the position is evaluated.
+    pub rollout_turns: u32,
+    /// Independent trees searched in parallel, merged by visit count at the root.
+    pub threads: usize,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 2000,
+            time_budget: None,
+            exploration: 1.4,
+            rollout_turns: 4,
+            threads: 1,
+        }
+    }
+}
+
+struct Node {
+    action: Option<Action>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Action>,
+    // the side whose choice led here, which is who the rewards are counted for
+    mover: Side,
+    visits: u32,
+    reward: f32,
+}
+
+struct Tree {
+    nodes: Vec<Node>,
+    root_side: Side,
+    config: MctsConfig,
+    evaluation: WeightedEvaluation,
+    rng: XorShiftRng,
+}
+
+impl Tree {
+    fn new(battle: &Battle, config: MctsConfig, evaluation: WeightedEvaluation, seed: u64) -> Self {
+        let mut rng = rng_from_seed(seed);
+        let untried = shuffled(&mut rng, actions(battle));
+        let root = Node {
+            action: None,
+            parent: None,
+            children: Vec::new(),
+            untried,
+            mover: battle.current_turn().opponent(),
+            visits: 0,
+            reward: 0.0,
+        };
+        Self {
+            nodes: vec![root],
+            root_side: battle.current_turn(),
+            config,
+            evaluation,
+            rng,
+        }
+    }
+
+    fn uct(&self, parent: usize, child: usize) -> f32 {
+        let child = &self.nodes[child];
+        let parent_visits = self.nodes[parent].visits.max(1) as f32;
+        let visits = child.visits.max(1) as f32;
+        child.reward / visits + self.config.exploration * (parent_visits.ln() / visits).sqrt()
+    }
+
+    fn select(&self, node: usize) -> usize {
+        let children = &self.nodes[node].children;
+        let mut best = children[0];
+        for &child in &children[1..] {
+            if self.uct(node, child) > self.uct(node, best) {
+                best = child;
+            }
+        }
+        best
+    }
+
+    fn iterate(&mut self, root_battle: &Battle) {
+        let mut battle = root_battle.clone();
+        let mut node = 0;
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select(node);
+            if let Some(action) = self.nodes[node].action {
+                let _ = action.apply(&mut battle);
+            }
+        }
+        if let Some(action) = self.nodes[node].untried.pop() {
+            let mover = battle.current_turn();
+            if action.apply(&mut battle).is_ok() {
+                let untried = shuffled(&mut self.rng, actions(&battle));
+                self.nodes.push(Node {
+                    action: Some(action),
+                    parent: Some(node),
+                    children: Vec::new(),
+                    untried,
+                    mover,
+                    visits: 0,
+                    reward: 0.0,
+                });
+                let child = self.nodes.len() - 1;
+                self.nodes[node].children.push(child);
+                node = child;
+            }
+        }
+        let reward = self.rollout(battle);
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.reward += if node.mover == self.root_side { reward } else { 1.0 - reward };
+            current = node.parent;
+        }
+    }
+
+    /// Plays the cheap default policy forward and scores the result in
+    /// [0, 1] for the root side.
+    fn rollout(&mut self, mut battle: Battle) -> f32 {
+        let mut policy = GreedyAi::new(self.rng.next_u64());
+        for _ in 0..self.config.rollout_turns * 2 {
+            if battle.is_over() {
+                break;
+            }
+            play_turn(&mut policy, &mut battle);
+        }
+        let score = self.evaluation.evaluate(&battle, self.root_side);
+        0.5 + 0.5 * (score / 20.0).tanh()
+    }
+
+    fn root_statistics(&self) -> Vec<(Action, u32, f32)> {
+        self.nodes[0]
+            .children
+            .iter()
+            .filter_map(|&child| {
+                let node = &self.nodes[child];
+                node.action.map(|action| (action, node.visits, node.reward))
+            })
+            .collect()
+    }
+}
+
+fn search(battle: Battle, config: MctsConfig, evaluation: WeightedEvaluation, seed: u64) -> Vec<(Action, u32, f32)> {
+    let deadline = config.time_budget.map(|budget| Instant::now() + budget);
+    let mut tree = Tree::new(&battle, config, evaluation, seed);
+    for _ in 0..config.iterations {
+        if deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false) {
+            break;
+        }
+        tree.iterate(&battle);
+    }
+    tree.root_statistics()
+}
+
+/// Monte Carlo tree search using UCT over unit actions.
+pub struct MctsAi {
+    config: MctsConfig,
+    evaluation: WeightedEvaluation,
+    rng: XorShiftRng,
+    plan: VecDeque<Command>,
+    planned_turn: Option<(Side, u32)>,
+}
+
+impl MctsAi {
+    pub fn new(config: MctsConfig, evaluation: WeightedEvaluation, seed: u64) -> Self {
+        Self {
+            config,
+            evaluation,
+            rng: rng_from_seed(seed),
+            plan: VecDeque::new(),
+            planned_turn: None,
+        }
+    }
+
+    pub fn best_action(&mut self, battle: &Battle) -> Action {
+        let seeds: Vec<u64> = (0..self.config.threads.max(1)).map(|_| self.rng.next_u64()).collect();
+        let statistics: Vec<(Action, u32, f32)> = if seeds.len() == 1 {
+            search(battle.clone(), self.config, self.evaluation, seeds[0])
+        } else {
+            let handles: Vec<_> = seeds
+                .into_iter()
+                .map(|seed| {
+                    let (battle, config, evaluation) = (battle.clone(), self.config, self.evaluation);
+                    thread::spawn(move || search(battle, config, evaluation, seed))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .filter_map(|handle| handle.join().ok())
+                .flat_map(|statistics| statistics.into_iter())
+                .collect()
+        };
+        let mut merged: Vec<(Action, u32)> = Vec::new();
+        for (action, visits, _) in statistics {
+            match merged.iter().position(|&(merged_action, _)| merged_action == action) {
+                Some(index) => merged[index].1 += visits,
+                None => merged.push((action, visits)),
+            }
+        }
+        merged
+            .into_iter()
+            .max_by_key(|&(_, visits)| visits)
+            .map(|(action, _)| action)
+            .unwrap_or(Action::EndTurn)
+    }
+}
+
+impl Ai for MctsAi {
+    fn name(&self) -> String {
+        "mcts".to_owned()
+    }
+
+    fn next_command(&mut self, battle: &Battle) -> Command {
+        let turn = (battle.current_turn(), battle.turn_number());
+        if self.planned_turn != Some(turn) {
+            self.plan.clear();
+            self.planned_turn = Some(turn);
+        }
+        if self.plan.is_empty() {
+            let action = self.best_action(battle);
+            self.plan.extend(action.commands());
+        }
+        self.plan.pop_front().unwrap_or(Command::EndTurn)
+    }
+}
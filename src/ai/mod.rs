@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+use battle::{Battle, BattleEvent, Command, RuleError};
+use console::{CommandRegistry, CommandResult};
+use game_object::*;
+use rand::{Rng, XorShiftRng};
+
+pub mod alpha_beta;
+pub mod analysis;
+pub mod evaluation;
+pub mod greedy;
+pub mod mcts;
+pub mod profile;
+
+pub use self::alpha_beta::{AlphaBetaAi, AlphaBetaConfig};
+pub use self::analysis::{Analysis, TileMap};
+pub use self::evaluation::{Evaluation, WeightedEvaluation};
+pub use self::greedy::GreedyAi;
+pub use self::mcts::{MctsAi, MctsConfig};
+pub use self::profile::{AiProfile, Personality, ProfiledAi};
+
+pub trait Ai {
+    fn name(&self) -> String;
+
+    /// Picks the next command for the side whose turn it is. Returning
+    /// `Command::EndTurn` hands control to the other side.
+    fn next_command(&mut self, battle: &Battle) -> Command;
+}
+
+/// Whether searches may also stop when their time budget runs out. Depth
+/// and node limits alone leave the seed deciding every game; a wall-clock
+/// limit makes the result depend on how fast the machine is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Timing {
+    /// Keeps the window responsive while the enemy thinks.
+    Interactive,
+    /// For the simulate and tournament tools, whose games must replay exactly.
+    Replayable,
+}
+
+pub fn names() -> Vec<&'static str> {
+    vec!["greedy", "alphabeta", "mcts", "mcts-parallel"]
+}
+
+/// Looks up a built-in strategy, or failing that a profile from the data
+/// files. These are for the tools, so they play replayably.
+pub fn by_name(name: &str, seed: u64) -> Option<Box<dyn Ai>> {
+    match name {
+        "greedy" => Some(Box::new(GreedyAi::new(seed))),
+        "alphabeta" => Some(Box::new(AlphaBetaAi::new(
+            AlphaBetaConfig::default(),
+            Box::new(WeightedEvaluation::default()),
+        ))),
+        "mcts" => Some(Box::new(MctsAi::new(MctsConfig::default(), WeightedEvaluation::default(), seed))),
+        "mcts-parallel" => Some(Box::new(MctsAi::new(
+            MctsConfig {
+                threads: 4,
+                ..MctsConfig::default()
+            },
+            WeightedEvaluation::default(),
+            seed,
+        ))),
+        _ => AiProfile::find(name).map(|profile| Box::new(ProfiledAi::new(profile, seed, Timing::Replayable)) as Box<dyn Ai>),
+    }
+}
+
+/// Everything one unit does in a turn: an optional move followed by an
+/// optional attack or ability. Search AIs branch on these rather than on
+/// raw commands.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Unit {
+        unit: UnitId,
+        to: Option<Tile>,
+        act: Option<Act>,
+    },
+    EndTurn,
+}
+
+/// What a unit does once it has moved.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Act {
+    Attack(UnitId),
+    Ability { index: usize, target: Tile },
+}
+
+impl Action {
+    pub fn commands(&self) -> Vec<Command> {
+        match *self {
+            Action::Unit { unit, to, act } => {
+                let mut commands = Vec::new();
+                if let Some(to) = to {
+                    commands.push(Command::Move { unit, to });
+                }
+                match act {
+                    Some(Act::Attack(target)) => commands.push(Command::Attack { unit, target }),
+                    Some(Act::Ability { index, target }) => {
+                        commands.push(Command::UseAbility { unit, ability: index, target })
+                    }
+                    None => (),
+                }
+                commands
+            }
+            Action::EndTurn => vec![Command::EndTurn],
+        }
+    }
+
+    pub fn apply(&self, battle: &mut Battle) -> Result<Vec<BattleEvent>, RuleError> {
+        let mut events = Vec::new();
+        for command in self.commands() {
+            events.extend(battle.apply(command)?);
+        }
+        Ok(events)
+    }
+}
+
+fn targets_from(battle: &Battle, unit_id: UnitId, from: Tile) -> Vec<UnitId> {
+    let side = match battle.units().get_unit(unit_id) {
+        Some(unit) => unit.get_side(),
+        None => return Vec::new(),
+    };
+    let mut targets: Vec<UnitId> = battle
+        .attack_tiles_from(unit_id, from)
+        .into_iter()
+        .filter_map(|tile| battle.units().unit_at(tile))
+        .filter(|target| battle.units().get_unit(*target).map(|t| t.get_side()) == Some(side.opponent()))
+        .collect();
+    targets.sort();
+    targets
+}
+
+/// Ability uses from `from` that would land on at least one unit or corpse.
+fn ability_uses_from(battle: &Battle, unit_id: UnitId, from: Tile) -> Vec<Act> {
+    let unit = match battle.units().get_unit(unit_id) {
+        Some(unit) => unit,
+        None => return Vec::new(),
+    };
+    let mut uses = Vec::new();
+    for index in battle.ready_abilities(unit_id) {
+        let ability = &unit.get_abilities()[index].ability;
+        for target in battle.ability_targets_from(unit_id, index, from) {
+            let lands = ability.area.iter().any(|offset| {
+                let tile = target + *offset;
+                battle.units().unit_at(tile).map(|id| id != unit_id).unwrap_or(false)
+                    || battle.corpse_at(tile).is_some()
+                    || (tile == from && ability.targeting == Targeting::Caster)
+            });
+            if lands {
+                uses.push(Act::Ability { index, target });
+            }
+        }
+    }
+    uses
+}
+
+/// Every action open to the side to move, ending the turn last.
+pub fn actions(battle: &Battle) -> Vec<Action> {
+    let mut actions = Vec::new();
+    if battle.is_over() {
+        return actions;
+    }
+    for unit in battle.units().ids_of(battle.current_turn()) {
+        let here = battle.units().get_unit(unit).unwrap().get_tile();
+        let mut destinations = vec![None];
+        if battle.can_move(unit) {
+            let mut tiles: Vec<Tile> = battle.reachable_tiles(unit).into_iter().collect();
+            tiles.sort_by_key(|tile| (tile.x(), tile.y()));
+            destinations.extend(tiles.into_iter().map(Some));
+        } else if !battle.can_attack(unit) {
+            continue;
+        }
+        for to in destinations {
+            let from = to.unwrap_or(here);
+            if battle.can_attack(unit) {
+                for target in targets_from(battle, unit, from) {
+                    actions.push(Action::Unit { unit, to, act: Some(Act::Attack(target)) });
+                }
+                for act in ability_uses_from(battle, unit, from) {
+                    actions.push(Action::Unit { unit, to, act: Some(act) });
+                }
+            }
+            if to.is_some() {
+                actions.push(Action::Unit { unit, to, act: None });
+            }
+        }
+    }
+    actions.push(Action::EndTurn);
+    actions
+}
+
+/// Plays commands from a script, ending the turn whenever it runs out.
+pub struct ScriptedAi {
+    commands: VecDeque<Command>,
+}
+
+impl ScriptedAi {
+    pub fn new(commands: Vec<Command>) -> Self {
+        Self {
+            commands: commands.into_iter().collect(),
+        }
+    }
+
+    pub fn parse(script: &str) -> Result<Self, String> {
+        let commands = script
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(Command::parse)
+            .collect::<Result<Vec<Command>, String>>()?;
+        Ok(Self::new(commands))
+    }
+}
+
+impl Ai for ScriptedAi {
+    fn name(&self) -> String {
+        "script".to_owned()
+    }
+
+    fn next_command(&mut self, _battle: &Battle) -> Command {
+        self.commands.pop_front().unwrap_or(Command::EndTurn)
+    }
+}
+
+pub fn shuffled<T>(rng: &mut XorShiftRng, mut items: Vec<T>) -> Vec<T> {
+    rng.shuffle(&mut items[..]);
+    items
+}
+
+/// Lets the AI issue commands until its turn is over. An illegal command
+/// forfeits the rest of the turn so a confused AI can't stall the game.
+pub fn play_turn(ai: &mut dyn Ai, battle: &mut Battle) {
+    let side = battle.current_turn();
+    while battle.current_turn() == side && !battle.is_over() {
+        let command = ai.next_command(battle);
+        if battle.apply(command).is_err() {
+            let _ = battle.apply(Command::EndTurn);
+        }
+    }
+}
+
+pub fn register_commands(registry: &mut CommandRegistry) {
+    registry.register("ai step", "ai step", step);
+}
+
+fn step(battle: &mut Battle, _args: &[&str]) -> CommandResult {
+    let mut ai = GreedyAi::new(battle.seed());
+    let command = ai.next_command(battle);
+    let events = battle.apply(command).map_err(|e| e.to_string())?;
+    let lines: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+    Ok(format!("{}\n{}", command, lines.join("\n")))
+}
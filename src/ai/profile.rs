@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+use ai::{actions, Action, AlphaBetaAi, AlphaBetaConfig, Ai, Timing, WeightedEvaluation};
+use battle::{rng_from_seed, Battle, Command};
+use map::fog::FogOfWar;
+use game_object::*;
+use rand::{Rng, XorShiftRng};
+use scenario::Scenario;
+use utils::find_resource;
+
+const PROFILES_FILE: &str = "ai/profiles.txt";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Personality {
+    Aggressive,
+    Defensive,
+    ProtectKing,
+    FocusHealers,
+}
+
+impl Personality {
+    pub fn from_name(name: &str) -> Option<Personality> {
+        match name {
+            "aggressive" => Some(Personality::Aggressive),
+            "defensive" => Some(Personality::Defensive),
+            "protect-king" => Some(Personality::ProtectKing),
+            "focus-healers" => Some(Personality::FocusHealers),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Personality::Aggressive => "aggressive",
+            Personality::Defensive => "defensive",
+            Personality::ProtectKing => "protect-king",
+            Personality::FocusHealers => "focus-healers",
+        }
+    }
+
+    pub fn weights(&self) -> WeightedEvaluation {
+        let base = WeightedEvaluation::default();
+        match *self {
+            Personality::Aggressive => WeightedEvaluation {
+                position: 0.3,
+                threat: 1.5,
+                danger: 0.1,
+                ..base
+            },
+            Personality::Defensive => WeightedEvaluation {
+                health: 2.0,
+                position: -0.05,
+                threat: 0.2,
+                danger: 1.0,
+                influence: 0.2,
+                ..base
+            },
+            Personality::ProtectKing => WeightedEvaluation { king: 10.0, ..base },
+            Personality::FocusHealers => WeightedEvaluation { healers: 3.0, ..base },
+        }
+    }
+}
+
+/// How well and in what style an AI plays. Read from `resources/ai/profiles.txt`:
+///
+/// ```text
+/// [veteran]
+/// personality defensive
+/// depth 4
+/// mistakes 0.05
+/// cheats no
+/// time-budget 200
+/// ```
+#[derive(Clone, Debug)]
+pub struct AiProfile {
+    pub name: String,
+    pub personality: Personality,
+    pub search_depth: u32,
+    /// Chance of playing a random action instead of the searched one.
+    pub mistake_rate: f32,
+    /// Whether the AI may look at units its side can't see.
+    pub cheats: bool,
+    /// Thinking allowed per search in the game, if limited. The tools leave
+    /// it out so their games replay from the seed.
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for AiProfile {
+    fn default() -> Self {
+        Self {
+            name: "normal".to_owned(),
+            personality: Personality::Aggressive,
+            search_depth: 3,
+            mistake_rate: 0.1,
+            cheats: false,
+            time_budget: Some(Duration::from_millis(200)),
+        }
+    }
+}
+
+impl AiProfile {
+    pub fn parse_all(text: &str) -> Result<Vec<AiProfile>, String> {
+        let mut profiles: Vec<AiProfile> = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                profiles.push(AiProfile {
+                    name: line[1..line.len() - 1].trim().to_owned(),
+                    ..AiProfile::default()
+                });
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let profile = profiles
+                .last_mut()
+                .ok_or_else(|| format!("line {}: setting outside of a [profile]", index + 1))?;
+            profile
+                .parse_setting(&words)
+                .map_err(|message| format!("line {}: {}", index + 1, message))?;
+        }
+        Ok(profiles)
+    }
+
+    fn parse_setting(&mut self, words: &[&str]) -> Result<(), String> {
+        let value = words.get(1).ok_or_else(|| format!("'{}' needs a value", words[0]))?;
+        match words[0] {
+            "personality" => {
+                self.personality = Personality::from_name(value)
+                    .ok_or_else(|| format!("unknown personality '{}'", value))?
+            }
+            "depth" => self.search_depth = value.parse().map_err(|_| "expected a depth".to_owned())?,
+            "mistakes" => self.mistake_rate = value.parse().map_err(|_| "expected a rate".to_owned())?,
+            "cheats" => self.cheats = *value == "yes",
+            "time-budget" => {
+                self.time_budget = match *value {
+                    "off" => None,
+                    value => {
+                        let millis = value.parse().map_err(|_| "expected milliseconds or 'off'".to_owned())?;
+                        Some(Duration::from_millis(millis))
+                    }
+                }
+            }
+            setting => return Err(format!("unknown setting '{}'", setting)),
+        }
+        Ok(())
+    }
+
+    /// Every profile in the resources directory, or just the default one if
+    /// the file is missing.
+    pub fn load_all() -> Result<Vec<AiProfile>, String> {
+        let path = match find_resource(Path::new(PROFILES_FILE)) {
+            Some(path) => path,
+            None => return Ok(vec![AiProfile::default()]),
+        };
+        let mut text = String::new();
+        File::open(&path)
+            .and_then(|mut file| file.read_to_string(&mut text))
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        AiProfile::parse_all(&text[..])
+    }
+
+    pub fn find(name: &str) -> Option<AiProfile> {
+        AiProfile::load_all()
+            .ok()
+            .and_then(|profiles| profiles.into_iter().find(|profile| profile.name == name))
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ({}, depth {}, {:.0}% mistakes{})",
+            self.name,
+            self.personality.name(),
+            self.search_depth,
+            self.mistake_rate * 100.0,
+            if self.cheats { ", cheats" } else { "" }
+        )
+    }
+}
+
+/// Alpha-beta search shaped by a profile's personality and difficulty.
+pub struct ProfiledAi {
+    profile: AiProfile,
+    search: AlphaBetaAi,
+    rng: XorShiftRng,
+    plan: VecDeque<Command>,
+    planned_turn: Option<(Side, u32)>,
+    fog: Option<FogOfWar>,
+}
+
+impl ProfiledAi {
+    pub fn new(profile: AiProfile, seed: u64, timing: Timing) -> Self {
+        let config = AlphaBetaConfig {
+            max_depth: profile.search_depth,
+            time_budget: match timing {
+                Timing::Interactive => profile.time_budget,
+                Timing::Replayable => None,
+            },
+            ..AlphaBetaConfig::default()
+        };
+        let search = AlphaBetaAi::new(config, Box::new(profile.personality.weights()));
+        Self {
+            profile,
+            search,
+            rng: rng_from_seed(seed),
+            plan: VecDeque::new(),
+            planned_turn: None,
+            fog: None,
+        }
+    }
+
+    pub fn profile(&self) -> &AiProfile {
+        &self.profile
+    }
+
+    pub fn restrict_to(&mut self, units: Vec<UnitId>) {
+        self.search.restrict_to(units);
+    }
+
+    /// What an honest AI gets to search: only enemies it can see or remembers.
+    fn view(&mut self, battle: &Battle) -> Battle {
+        let side = battle.current_turn();
+        let fog = self.fog.get_or_insert_with(|| FogOfWar::with_known_positions(side, battle));
+        fog.update(battle);
+        fog.view(battle)
+    }
+
+    fn choose_action(&mut self, battle: &Battle) -> Action {
+        if self.rng.next_f32() < self.profile.mistake_rate {
+            let choices: Vec<Action> = actions(battle)
+                .into_iter()
+                .filter(|action| self.search.allows(battle, action))
+                .collect();
+            if let Some(action) = self.rng.choose(&choices[..]) {
+                return *action;
+            }
+        }
+        self.search.best_action(battle)
+    }
+}
+
+impl Ai for ProfiledAi {
+    fn name(&self) -> String {
+        self.profile.name.clone()
+    }
+
+    fn next_command(&mut self, battle: &Battle) -> Command {
+        let turn = (battle.current_turn(), battle.turn_number());
+        if self.planned_turn != Some(turn) {
+            self.plan.clear();
+            self.planned_turn = Some(turn);
+        }
+        if self.plan.is_empty() {
+            let action = if self.profile.cheats || !battle.fog_of_war() {
+                self.choose_action(battle)
+            } else {
+                let view = self.view(battle);
+                self.choose_action(&view)
+            };
+            self.plan.extend(action.commands());
+        }
+        self.plan.pop_front().unwrap_or(Command::EndTurn)
+    }
+}
+
+/// Plays one side as several squads, each with its own profile. Each squad
+/// moves in turn; the side's turn ends once every squad has finished.
+pub struct SquadAi {
+    squads: Vec<ProfiledAi>,
+    current: usize,
+    planned_turn: Option<(Side, u32)>,
+}
+
+impl SquadAi {
+    pub fn new(squads: Vec<ProfiledAi>) -> Self {
+        Self {
+            squads,
+            current: 0,
+            planned_turn: None,
+        }
+    }
+}
+
+impl Ai for SquadAi {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.squads.iter().map(|squad| squad.name()).collect();
+        names.join("+")
+    }
+
+    fn next_command(&mut self, battle: &Battle) -> Command {
+        let turn = (battle.current_turn(), battle.turn_number());
+        if self.planned_turn != Some(turn) {
+            self.current = 0;
+            self.planned_turn = Some(turn);
+        }
+        while self.current < self.squads.len() {
+            match self.squads[self.current].next_command(battle) {
+                Command::EndTurn => self.current += 1,
+                command => return command,
+            }
+        }
+        Command::EndTurn
+    }
+}
+
+/// Builds the AI a scenario asks for on `side`, if it names any profiles.
+pub fn scenario_ai(
+    scenario: &Scenario,
+    side: Side,
+    battle: &Battle,
+    seed: u64,
+    timing: Timing,
+) -> Result<Option<Box<dyn Ai>>, String> {
+    let profiles = AiProfile::load_all()?;
+    let lookup = |name: &str| {
+        profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+            .ok_or_else(|| format!("unknown AI profile '{}'", name))
+    };
+    let mut squads = Vec::new();
+    let mut claimed = Vec::new();
+    for (index, &(ref squad, ref profile)) in scenario.squads.iter().enumerate() {
+        let units: Vec<UnitId> = scenario
+            .units
+            .iter()
+            .filter(|placement| placement.side == side && placement.squad.as_ref() == Some(squad))
+            .filter_map(|placement| battle.units().unit_at(placement.tile))
+            .collect();
+        if units.is_empty() {
+            continue;
+        }
+        claimed.extend(units.iter().cloned());
+        let mut ai = ProfiledAi::new(lookup(profile)?, seed.wrapping_add(index as u64), timing);
+        ai.restrict_to(units);
+        squads.push(ai);
+    }
+    if let Some(profile) = scenario.profile_for(side) {
+        let rest: Vec<UnitId> = battle
+            .units()
+            .ids_of(side)
+            .into_iter()
+            .filter(|id| !claimed.contains(id))
+            .collect();
+        let mut ai = ProfiledAi::new(lookup(profile)?, seed, timing);
+        if !squads.is_empty() {
+            ai.restrict_to(rest);
+        }
+        squads.push(ai);
+    }
+    Ok(match squads.len() {
+        0 => None,
+        1 => Some(Box::new(squads.pop().unwrap())),
+        _ => Some(Box::new(SquadAi::new(squads))),
+    })
+}
@@ -0,0 +1,38 @@
+#[cfg(feature = "graphics")]
+mod sprites;
+
+#[cfg(feature = "graphics")]
+pub use self::sprites::Sprites;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpriteName {
+    Archer,
+    Wizard,
+    Warrior,
+    Scholar,
+    Necromancer,
+    Defender,
+    Monk,
+    Champion,
+    King,
+    Bodyguard,
+    UndeadArcher,
+    UndeadWizard,
+    UndeadWarrior,
+    UndeadScholar,
+    UndeadNecromancer,
+    UndeadDefender,
+    UndeadMonk,
+    UndeadChampion,
+    UndeadKing,
+    UndeadBodyguard,
+    Floor,
+    Rubble,
+    Wall,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub enum SpriteSheet {
+    Main,
+    BricksAndTiles,
+}
@@ -1,41 +1,10 @@
 use ggez::graphics;
 use ggez::graphics::{DrawParam, Image, Point2, Rect};
 use ggez::Context;
-use main_state::PIXELS_PER_TILE;
-use main_state::SCALE_FACTOR;
 use std::collections::hash_map::HashMap;
 use std::path::Path;
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub enum SpriteName {
-    Archer,
-    Wizard,
-    Warrior,
-    Scholar,
-    Necromancer,
-    Defender,
-    Monk,
-    Champion,
-    King,
-    Bodyguard,
-    UndeadArcher,
-    UndeadWizard,
-    UndeadWarrior,
-    UndeadScholar,
-    UndeadNecromancer,
-    UndeadDefender,
-    UndeadMonk,
-    UndeadChampion,
-    UndeadKing,
-    UndeadBodyguard,
-    Floor,
-}
-
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
-pub enum SpriteSheet {
-    Main,
-    BricksAndTiles,
-}
+use viewport::PIXELS_PER_TILE;
+use super::{SpriteName, SpriteSheet};
 
 #[derive(Clone, Copy)]
 struct Sprite {
@@ -84,6 +53,8 @@ impl Sprites {
         new_sprites.sprite_default(SpriteName::UndeadKing, SpriteSheet::Main, 8, 3);
         new_sprites.sprite_default(SpriteName::UndeadBodyguard, SpriteSheet::Main, 9, 3);
         new_sprites.sprite_default(SpriteName::Floor, SpriteSheet::BricksAndTiles, 5, 1);
+        new_sprites.sprite_default(SpriteName::Rubble, SpriteSheet::BricksAndTiles, 2, 4);
+        new_sprites.sprite_default(SpriteName::Wall, SpriteSheet::BricksAndTiles, 0, 0);
         new_sprites
     }
 
@@ -151,7 +122,6 @@ impl Sprites {
             DrawParam {
                 src: sprite.src,
                 dest,
-                scale: Point2::new(SCALE_FACTOR, SCALE_FACTOR),
                 ..Default::default()
             },
         ).unwrap();
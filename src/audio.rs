@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use ggez::audio::Source;
+use ggez::Context;
+use event_bus::{GameEvent, Subscriber};
+use settings::Settings;
+use utils::find_resource;
+
+const AUDIO_DIR: &str = "audio";
+const AUDIO_FILE: &str = "audio.txt";
+/// Seconds it takes one music track to fade into the next.
+const CROSSFADE: f32 = 1.5;
+
+#[derive(Debug)]
+pub enum AudioError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AudioError::Io(ref e) => write!(f, "could not read audio config: {}", e),
+            AudioError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for AudioError {
+    fn description(&self) -> &str {
+        "invalid audio config"
+    }
+}
+
+impl From<io::Error> for AudioError {
+    fn from(e: io::Error) -> Self {
+        AudioError::Io(e)
+    }
+}
+
+/// Which files make up the game's sounds and music, and which cues play
+/// them, read from `resources/audio/audio.txt`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AudioConfig {
+    /// Sound effect names and their files.
+    pub sounds: HashMap<String, String>,
+    /// Cues and the sound effect each plays.
+    pub cues: HashMap<String, String>,
+    /// Scene names and the music file that loops while they show.
+    pub music: HashMap<String, String>,
+}
+
+impl AudioConfig {
+    pub fn parse(text: &str) -> Result<AudioConfig, AudioError> {
+        let mut config = AudioConfig::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            config
+                .parse_directive(&words)
+                .map_err(|message| AudioError::Parse { line: index + 1, message })?;
+        }
+        Ok(config)
+    }
+
+    fn parse_directive(&mut self, words: &[&str]) -> Result<(), String> {
+        let (first, second) = match (words.get(1), words.get(2)) {
+            (Some(first), Some(second)) => (first.to_string(), second.to_string()),
+            _ => return Err(format!("'{}' takes a name and a value", words[0])),
+        };
+        match words[0] {
+            "sound" => {
+                self.sounds.insert(first, second);
+            }
+            "on" => {
+                if !self.sounds.contains_key(&second) {
+                    return Err(format!("unknown sound '{}'", second));
+                }
+                self.cues.insert(first, second);
+            }
+            "music" => {
+                self.music.insert(first, second);
+            }
+            directive => return Err(format!("unknown directive '{}'", directive)),
+        }
+        Ok(())
+    }
+
+    /// The config in the resources directory, or nothing if there is none.
+    pub fn load() -> Result<AudioConfig, AudioError> {
+        let path = match find_resource(&Path::new(AUDIO_DIR).join(AUDIO_FILE)) {
+            Some(path) => path,
+            None => return Ok(AudioConfig::default()),
+        };
+        let mut text = String::new();
+        File::open(&path)?.read_to_string(&mut text)?;
+        AudioConfig::parse(&text[..])
+    }
+}
+
+/// A piece of music fading in or out. `source` is missing if the file
+/// couldn't be played, so the scene stays quiet instead of retrying.
+struct Track {
+    scene: String,
+    source: Option<Source>,
+    /// How far faded in it is, from 0 to 1.
+    fade: f32,
+}
+
+/// Plays sound effects for cues and music for scenes. Anything that can't
+/// be loaded or played is skipped, so without an audio device the game
+/// simply runs silent.
+pub struct Audio {
+    config: AudioConfig,
+    sounds: HashMap<String, Source>,
+    music: Option<Track>,
+    fading_out: Option<Track>,
+    music_gain: f32,
+    effects_gain: f32,
+    /// Set once playing fails, after which nothing is tried again.
+    silent: bool,
+    /// What went wrong since the last time they were taken.
+    warnings: Vec<String>,
+}
+
+impl Audio {
+    pub fn new(ctx: &mut Context, settings: &Settings) -> Audio {
+        let mut warnings = Vec::new();
+        let config = AudioConfig::load().unwrap_or_else(|e| {
+            warnings.push(format!("audio: {}", e));
+            AudioConfig::default()
+        });
+        let mut sounds = HashMap::new();
+        let mut missing = Vec::new();
+        for (name, file) in &config.sounds {
+            match Source::new(ctx, resource_path(file)) {
+                Ok(source) => {
+                    sounds.insert(name.clone(), source);
+                }
+                Err(_) => missing.push(name.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            missing.sort();
+            warnings.push(format!("audio: could not load {}", missing.join(", ")));
+        }
+        let mut audio = Audio {
+            config,
+            sounds,
+            music: None,
+            fading_out: None,
+            music_gain: 0.0,
+            effects_gain: 0.0,
+            silent: false,
+            warnings,
+        };
+        audio.set_volumes(settings);
+        audio
+    }
+
+    pub fn set_volumes(&mut self, settings: &Settings) {
+        self.music_gain = settings.music_gain();
+        self.effects_gain = settings.effects_gain();
+        for source in self.sounds.values_mut() {
+            source.set_volume(self.effects_gain);
+        }
+        self.apply_fades();
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        self.warnings.drain(..).collect()
+    }
+
+    /// Plays whatever sound the cue is mapped to, if any.
+    pub fn cue(&mut self, cue: &str) {
+        if self.silent {
+            return;
+        }
+        let source = match self.config.cues.get(cue).and_then(|sound| self.sounds.get(sound)) {
+            Some(source) => source,
+            None => return,
+        };
+        if let Err(e) = source.play() {
+            self.warnings.push(format!("audio: {}, going silent", e));
+            self.silent = true;
+        }
+    }
+
+    /// Fades over to the scene's music, unless it's already playing.
+    pub fn play_music(&mut self, ctx: &mut Context, scene: &str) {
+        if self.music.as_ref().map(|track| &track.scene[..]) == Some(scene) {
+            return;
+        }
+        if let Some(old) = self.fading_out.take() {
+            stop(old);
+        }
+        self.fading_out = self.music.take();
+        let source = if self.silent {
+            None
+        } else {
+            self.config
+                .music
+                .get(scene)
+                .and_then(|file| Source::new(ctx, resource_path(file)).ok())
+                .and_then(|mut source| {
+                    source.set_repeat(true);
+                    source.set_volume(0.0);
+                    source.play().ok().map(|_| source)
+                })
+        };
+        self.music = Some(Track {
+            scene: scene.to_owned(),
+            source,
+            fade: 0.0,
+        });
+    }
+
+    /// Moves crossfades along by `seconds`.
+    pub fn update(&mut self, seconds: f32) {
+        let step = seconds / CROSSFADE;
+        if let Some(ref mut track) = self.music {
+            track.fade = (track.fade + step).min(1.0);
+        }
+        let faded = match self.fading_out {
+            Some(ref mut track) => {
+                track.fade -= step;
+                track.fade <= 0.0
+            }
+            None => false,
+        };
+        if faded {
+            stop(self.fading_out.take().unwrap());
+        }
+        self.apply_fades();
+    }
+
+    fn apply_fades(&mut self) {
+        let gain = self.music_gain;
+        for track in self.music.iter_mut().chain(self.fading_out.iter_mut()) {
+            if let Some(ref mut source) = track.source {
+                source.set_volume(track.fade.max(0.0) * gain);
+            }
+        }
+    }
+}
+
+impl Subscriber for Audio {
+    fn notify(&mut self, event: &GameEvent) {
+        self.cue(event.name());
+    }
+}
+
+fn stop(track: Track) {
+    if let Some(source) = track.source {
+        source.stop();
+    }
+}
+
+fn resource_path(file: &str) -> String {
+    format!("/{}/{}", AUDIO_DIR, file)
+}
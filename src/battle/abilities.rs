@@ -0,0 +1,168 @@
+use battle::{Battle, BattleEvent, RuleError, TurnState};
+use game_object::*;
+use map::line_of_sight::has_line_of_sight;
+
+impl Battle {
+    /// Indices of the unit's abilities that are off cooldown.
+    pub fn ready_abilities(&self, unit_id: UnitId) -> Vec<usize> {
+        match self.units.get_unit(unit_id) {
+            Some(unit) => unit.get_abilities()
+                .iter()
+                .enumerate()
+                .filter(|&(_, slot)| slot.is_ready())
+                .map(|(index, _)| index)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Tiles the ability could be aimed at right now.
+    pub fn ability_targets(&self, unit_id: UnitId, index: usize) -> Vec<Tile> {
+        match self.units.get_unit(unit_id) {
+            Some(unit) => self.ability_targets_from(unit_id, index, unit.get_tile()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Tiles the ability could be aimed at if the unit stood on `from`.
+    pub fn ability_targets_from(&self, unit_id: UnitId, index: usize, from: Tile) -> Vec<Tile> {
+        let (side, slot) = match self.units.get_unit(unit_id) {
+            Some(unit) => match unit.get_ability(index) {
+                Some(slot) => (unit.get_side(), slot),
+                None => return Vec::new(),
+            },
+            None => return Vec::new(),
+        };
+        let ability = &slot.ability;
+        if ability.targeting == Targeting::Caster {
+            return vec![from];
+        }
+        let mut targets: Vec<Tile> = ability
+            .range
+            .iter()
+            .map(|offset| from + *offset)
+            .filter(|tile| self.in_bounds(*tile))
+            .filter(|tile| from.distance(*tile) <= 1 || has_line_of_sight(self, from, *tile, Some(unit_id)))
+            .filter(|tile| self.is_valid_target(unit_id, side, ability.targeting, *tile))
+            .collect();
+        targets.sort_by_key(|tile| (tile.x(), tile.y()));
+        targets
+    }
+
+    fn is_valid_target(&self, unit_id: UnitId, side: Side, targeting: Targeting, tile: Tile) -> bool {
+        let occupant = self.units
+            .unit_at(tile)
+            .filter(|id| *id != unit_id)
+            .and_then(|id| self.units.get_unit(id));
+        match targeting {
+            Targeting::Caster => false,
+            Targeting::Ally => occupant.map(|unit| unit.get_side() == side).unwrap_or(false),
+            Targeting::Enemy => occupant.map(|unit| unit.get_side() != side).unwrap_or(false),
+            Targeting::Tile => self.map.terrain(tile).move_cost().is_some(),
+            Targeting::Corpse => occupant.is_none() && self.corpse_at(tile).is_some(),
+        }
+    }
+
+    pub(super) fn use_ability(&mut self, unit_id: UnitId, index: usize, target: Tile) -> Result<Vec<BattleEvent>, RuleError> {
+        let (side, slot) = {
+            let unit = self.acting_unit(unit_id)?;
+            let slot = unit.get_ability(index).ok_or(RuleError::NoSuchAbility(unit_id, index))?;
+            (unit.get_side(), slot.clone())
+        };
+        if !self.can_attack(unit_id) {
+            return Err(RuleError::AlreadyAttacked(unit_id));
+        }
+        if !slot.is_ready() {
+            return Err(RuleError::OnCooldown(unit_id, index));
+        }
+        if !self.ability_targets(unit_id, index).contains(&target) {
+            return Err(RuleError::InvalidTarget(target));
+        }
+        {
+            let state = self.turn_states.entry(unit_id).or_insert_with(TurnState::default);
+            state.attacked = true;
+            state.ability = Some(index);
+        }
+        self.units.get_unit_mut(unit_id).unwrap().start_cooldown(index);
+
+        let ability = slot.ability;
+        let mut events = vec![BattleEvent::AbilityUsed {
+            unit: unit_id,
+            ability: ability.name,
+            target,
+        }];
+        let mut area: Vec<Tile> = ability.area.iter().map(|offset| target + *offset).collect();
+        area.sort_by_key(|tile| (tile.x(), tile.y()));
+        for tile in area {
+            for effect in &ability.effects {
+                events.extend(self.apply_ability_effect(unit_id, side, *effect, tile));
+            }
+        }
+        Ok(events)
+    }
+
+    fn apply_ability_effect(&mut self, caster: UnitId, side: Side, effect: AbilityEffect, tile: Tile) -> Vec<BattleEvent> {
+        if let AbilityEffect::Raise(health) = effect {
+            return self.raise(side, health, tile).into_iter().collect();
+        }
+        let target_id = match self.units.unit_at(tile) {
+            Some(target_id) => target_id,
+            None => return Vec::new(),
+        };
+        let is_enemy = self.units.get_unit(target_id).map(|unit| unit.get_side() != side).unwrap_or(false);
+        if effect.is_harmful() != is_enemy {
+            return Vec::new();
+        }
+        let target = self.units.get_unit_mut(target_id).unwrap();
+        match effect {
+            AbilityEffect::Damage(damage, damage_type) => {
+                let report = target.receive_hit(damage, damage_type);
+                let mut events = vec![BattleEvent::UnitDamaged {
+                    attacker: caster,
+                    target: target_id,
+                    report,
+                }];
+                if report.killed {
+                    events.push(self.kill(target_id));
+                }
+                events
+            }
+            AbilityEffect::Heal(amount) => {
+                let health = target.get_health_mut();
+                let (amount, shielded) = health.overheal(amount);
+                vec![BattleEvent::UnitHealed {
+                    healer: caster,
+                    target: target_id,
+                    amount,
+                    shielded,
+                    remaining: health.get_current(),
+                }]
+            }
+            AbilityEffect::Status(status) => {
+                target.get_effects_mut().add(status);
+                vec![BattleEvent::EffectApplied {
+                    unit: target_id,
+                    effect: status,
+                }]
+            }
+            AbilityEffect::Raise(_) => Vec::new(),
+        }
+    }
+
+    /// Brings the corpse on `tile` back to fight for `side`.
+    fn raise(&mut self, side: Side, health: u16, tile: Tile) -> Option<BattleEvent> {
+        if self.units.unit_at(tile).is_some() {
+            return None;
+        }
+        let index = self.corpses.iter().rposition(|corpse| corpse.get_tile() == tile)?;
+        let corpse = self.corpses.remove(index);
+        let mut unit = corpse.get_class().spawn(tile, side);
+        {
+            let unit_health = unit.get_health_mut();
+            let missing = unit_health.get_max().saturating_sub(health);
+            unit_health.take_damage(missing);
+        }
+        let unit_id = self.units.make_unit(unit);
+        Some(BattleEvent::UnitRaised { unit: unit_id, tile })
+    }
+}
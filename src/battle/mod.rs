@@ -0,0 +1,894 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use event_bus::GameEvent;
+use game_object::effects::Tick;
+use game_object::*;
+use map::fog;
+use map::line_of_sight::has_line_of_sight;
+use map::pathfinding::{Path, Pathfinder};
+use map::Map;
+use rand::{SeedableRng, XorShiftRng};
+
+mod abilities;
+mod objectives;
+
+pub use self::objectives::{Condition, Objective};
+
+pub fn rng_from_seed(seed: u64) -> XorShiftRng {
+    // xorshift can't be seeded with all zeroes, so the seed is mixed with fixed words
+    XorShiftRng::from_seed([0x193a_6754, seed as u32, (seed >> 32) as u32, 0x9e37_79b9])
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    Move { unit: UnitId, to: Tile },
+    Attack { unit: UnitId, target: UnitId },
+    /// Uses the unit's ability at `ability` in its list, aimed at `target`.
+    UseAbility { unit: UnitId, ability: usize, target: Tile },
+    EndTurn,
+}
+
+impl Command {
+    /// Parses the textual form used by scripts: `move <id> <x> <y>`, `attack <id> <target>`,
+    /// `ability <id> <index> <x> <y>` or `end`.
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let id = |i: usize| parse_word::<u32>(&words, i, line).map(UnitId::new);
+        let number = |i: usize| parse_word::<i16>(&words, i, line);
+        match words.get(0) {
+            Some(&"move") => Ok(Command::Move {
+                unit: id(1)?,
+                to: Tile::new(number(2)?, number(3)?),
+            }),
+            Some(&"attack") => Ok(Command::Attack {
+                unit: id(1)?,
+                target: id(2)?,
+            }),
+            Some(&"ability") => Ok(Command::UseAbility {
+                unit: id(1)?,
+                ability: parse_word(&words, 2, line)?,
+                target: Tile::new(number(3)?, number(4)?),
+            }),
+            Some(&"end") => Ok(Command::EndTurn),
+            _ => Err(format!("unknown command '{}'", line)),
+        }
+    }
+}
+
+fn parse_word<T: FromStr>(words: &[&str], i: usize, line: &str) -> Result<T, String> {
+    words.get(i)
+        .and_then(|word| word.parse().ok())
+        .ok_or_else(|| format!("expected a number in '{}'", line))
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Command::Move { unit, to } => write!(f, "move {} {} {}", unit, to.x(), to.y()),
+            Command::Attack { unit, target } => write!(f, "attack {} {}", unit, target),
+            Command::UseAbility { unit, ability, target } => {
+                write!(f, "ability {} {} {} {}", unit, ability, target.x(), target.y())
+            }
+            Command::EndTurn => write!(f, "end"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BattleEvent {
+    /// `path` is every tile stepped on after `from`, ending with `to`.
+    UnitMoved { unit: UnitId, from: Tile, to: Tile, path: Vec<Tile> },
+    UnitDamaged { attacker: UnitId, target: UnitId, report: DamageReport },
+    UnitDied { unit: UnitId },
+    TurnEnded { side: Side, next: Side },
+    EffectApplied { unit: UnitId, effect: StatusEffect },
+    /// A status effect hurt or healed its unit at the start of a turn.
+    EffectTicked { unit: UnitId, kind: EffectKind, change: i16, remaining: i16 },
+    EffectExpired { unit: UnitId, kind: EffectKind },
+    AbilityUsed { unit: UnitId, ability: &'static str, target: Tile },
+    /// Healing past full health becomes `shielded` temporary hit points.
+    UnitHealed { healer: UnitId, target: UnitId, amount: u16, shielded: u16, remaining: i16 },
+    /// A corpse was brought back as a new unit.
+    UnitRaised { unit: UnitId, tile: Tile },
+    /// `objective` decided the battle in `winner`'s favour.
+    BattleOver { winner: Side, objective: Objective },
+}
+
+impl BattleEvent {
+    /// A short name for the kind of event, as used in data files.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            BattleEvent::UnitMoved { .. } => "unit-moved",
+            BattleEvent::UnitDamaged { .. } => "unit-damaged",
+            BattleEvent::UnitDied { .. } => "unit-died",
+            BattleEvent::TurnEnded { .. } => "turn-ended",
+            BattleEvent::EffectApplied { .. } => "effect-applied",
+            BattleEvent::EffectTicked { .. } => "effect-ticked",
+            BattleEvent::EffectExpired { .. } => "effect-expired",
+            BattleEvent::AbilityUsed { .. } => "ability-used",
+            BattleEvent::UnitHealed { .. } => "unit-healed",
+            BattleEvent::UnitRaised { .. } => "unit-raised",
+            BattleEvent::BattleOver { .. } => "battle-over",
+        }
+    }
+}
+
+impl fmt::Display for BattleEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BattleEvent::UnitMoved { unit, from, to, .. } => write!(
+                f,
+                "unit {} moved {},{} -> {},{}",
+                unit,
+                from.x(),
+                from.y(),
+                to.x(),
+                to.y()
+            ),
+            BattleEvent::UnitDamaged { attacker, target, report } => {
+                write!(f, "unit {} hit unit {} for {}", attacker, target, report)
+            }
+            BattleEvent::UnitDied { unit } => write!(f, "unit {} died", unit),
+            BattleEvent::TurnEnded { side, next } => {
+                write!(f, "{} ended their turn, {} to move", side.name(), next.name())
+            }
+            BattleEvent::EffectApplied { unit, effect } => write!(
+                f,
+                "unit {} is affected by {} {} for {} turns",
+                unit, effect.kind, effect.magnitude, effect.turns
+            ),
+            BattleEvent::EffectTicked { unit, kind, change, remaining } => {
+                write!(f, "unit {} {} {:+} ({} left)", unit, kind, change, remaining)
+            }
+            BattleEvent::EffectExpired { unit, kind } => write!(f, "unit {}'s {} wore off", unit, kind),
+            BattleEvent::AbilityUsed { unit, ability, target } => {
+                write!(f, "unit {} used {} on {},{}", unit, ability, target.x(), target.y())
+            }
+            BattleEvent::UnitHealed { healer, target, amount, shielded, remaining } => {
+                write!(f, "unit {} healed unit {} for {}", healer, target, amount)?;
+                if shielded > 0 {
+                    write!(f, ", {} shield", shielded)?;
+                }
+                write!(f, " ({} left)", remaining)
+            }
+            BattleEvent::UnitRaised { unit, tile } => {
+                write!(f, "unit {} rose at {},{}", unit, tile.x(), tile.y())
+            }
+            BattleEvent::BattleOver { winner, objective } => {
+                write!(f, "{} won ({} to {})", winner.name(), objective.side.name(), objective)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum RuleError {
+    NoSuchUnit(UnitId),
+    NotYourTurn(UnitId),
+    AlreadyMoved(UnitId),
+    AlreadyAttacked(UnitId),
+    Unreachable(Tile),
+    OutOfRange(UnitId),
+    FriendlyTarget(UnitId),
+    Stunned(UnitId),
+    NoSuchAbility(UnitId, usize),
+    OnCooldown(UnitId, usize),
+    InvalidTarget(Tile),
+    BattleOver,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RuleError::NoSuchUnit(id) => write!(f, "there is no unit {}", id),
+            RuleError::NotYourTurn(id) => write!(f, "unit {} can't act this turn", id),
+            RuleError::AlreadyMoved(id) => write!(f, "unit {} has already moved", id),
+            RuleError::AlreadyAttacked(id) => write!(f, "unit {} has already attacked", id),
+            RuleError::Unreachable(tile) => write!(f, "tile {},{} is out of reach", tile.x(), tile.y()),
+            RuleError::OutOfRange(id) => write!(f, "unit {} is out of range", id),
+            RuleError::FriendlyTarget(id) => write!(f, "unit {} is on the same side", id),
+            RuleError::Stunned(id) => write!(f, "unit {} is stunned", id),
+            RuleError::NoSuchAbility(id, index) => write!(f, "unit {} has no ability {}", id, index),
+            RuleError::OnCooldown(id, index) => write!(f, "unit {}'s ability {} isn't ready", id, index),
+            RuleError::InvalidTarget(tile) => write!(f, "tile {},{} isn't a valid target", tile.x(), tile.y()),
+            RuleError::BattleOver => write!(f, "the battle is over"),
+        }
+    }
+}
+
+impl Error for RuleError {
+    fn description(&self) -> &str {
+        "an illegal command was issued"
+    }
+}
+
+#[derive(Clone, Copy, Default, Hash)]
+struct TurnState {
+    moved: bool,
+    attacked: bool,
+    /// The ability used this turn, whose cooldown only starts counting next turn.
+    ability: Option<usize>,
+}
+
+pub struct Battle {
+    units: Units,
+    corpses: Vec<Unit>,
+    map: Map,
+    zone_of_control: bool,
+    units_block_sight: bool,
+    fog_of_war: bool,
+    current_turn: Side,
+    turn_number: u32,
+    turn_states: HashMap<UnitId, TurnState>,
+    objectives: Vec<Objective>,
+    seed: u64,
+    rng: XorShiftRng,
+    /// Everything the rules have made happen that hasn't been taken for
+    /// the event bus yet.
+    events: Vec<GameEvent>,
+    /// Off for copies, which are only ever what-ifs nobody is told about.
+    publishing: bool,
+}
+
+impl Clone for Battle {
+    fn clone(&self) -> Self {
+        Self {
+            units: self.units.clone(),
+            corpses: self.corpses.clone(),
+            map: self.map.clone(),
+            zone_of_control: self.zone_of_control,
+            units_block_sight: self.units_block_sight,
+            fog_of_war: self.fog_of_war,
+            current_turn: self.current_turn,
+            turn_number: self.turn_number,
+            turn_states: self.turn_states.clone(),
+            objectives: self.objectives.clone(),
+            seed: self.seed,
+            rng: self.rng.clone(),
+            events: Vec::new(),
+            publishing: false,
+        }
+    }
+}
+
+impl Battle {
+    pub fn new(seed: u64) -> Self {
+        Self::with_size(seed, 10, 10)
+    }
+
+    pub fn with_size(seed: u64, width: i32, height: i32) -> Self {
+        Self {
+            units: Units::new(),
+            corpses: Vec::new(),
+            map: Map::new(width, height),
+            zone_of_control: false,
+            units_block_sight: false,
+            fog_of_war: false,
+            current_turn: Side::Player,
+            turn_number: 1,
+            turn_states: HashMap::new(),
+            objectives: Vec::new(),
+            seed,
+            rng: rng_from_seed(seed),
+            events: Vec::new(),
+            publishing: true,
+        }
+    }
+
+    pub fn units(&self) -> &Units {
+        &self.units
+    }
+
+    pub fn units_mut(&mut self) -> &mut Units {
+        &mut self.units
+    }
+
+    /// Units that died in this battle, lying where they fell.
+    pub fn corpses(&self) -> &[Unit] {
+        &self.corpses
+    }
+
+    pub fn corpse_at(&self, tile: Tile) -> Option<&Unit> {
+        self.corpses.iter().rev().find(|corpse| corpse.get_tile() == tile)
+    }
+
+    /// Removes a unit that has run out of health, leaving its corpse behind.
+    fn kill(&mut self, unit_id: UnitId) -> BattleEvent {
+        if let Some(unit) = self.units.remove_unit(unit_id) {
+            self.corpses.push(unit);
+        }
+        BattleEvent::UnitDied { unit: unit_id }
+    }
+
+    /// Kills the unit outright, whatever its health.
+    pub fn slay(&mut self, unit_id: UnitId) -> Result<Vec<BattleEvent>, RuleError> {
+        self.units.get_unit(unit_id).ok_or(RuleError::NoSuchUnit(unit_id))?;
+        let (side, turn) = (self.current_turn, self.turn_number);
+        let mut events = vec![self.kill(unit_id)];
+        if let Some((winner, objective)) = self.outcome() {
+            events.push(BattleEvent::BattleOver { winner, objective });
+        }
+        self.publish_all(side, turn, &events);
+        Ok(events)
+    }
+
+    /// Puts the unit back to full health, without any shield.
+    pub fn restore(&mut self, unit_id: UnitId) -> Result<BattleEvent, RuleError> {
+        let (side, turn) = (self.current_turn, self.turn_number);
+        let health = self.units
+            .get_unit_mut(unit_id)
+            .ok_or(RuleError::NoSuchUnit(unit_id))?
+            .get_health_mut();
+        let amount = (health.get_max() as i16 - health.get_current().max(0)) as u16;
+        health.restore();
+        let event = BattleEvent::UnitHealed {
+            healer: unit_id,
+            target: unit_id,
+            amount,
+            shielded: 0,
+            remaining: health.get_current(),
+        };
+        self.publish(side, turn, event.clone());
+        Ok(event)
+    }
+
+    /// Queues an event that happened on `side`'s turn `turn` to be taken.
+    fn publish(&mut self, side: Side, turn: u32, event: BattleEvent) {
+        if self.publishing {
+            self.events.push(GameEvent::Battle { side, turn, event });
+        }
+    }
+
+    fn publish_all(&mut self, side: Side, turn: u32, events: &[BattleEvent]) {
+        if !self.publishing {
+            return;
+        }
+        for event in events {
+            self.publish(side, turn, event.clone());
+        }
+    }
+
+    /// Everything that has happened since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain(..).collect()
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn map_mut(&mut self) -> &mut Map {
+        &mut self.map
+    }
+
+    pub fn width(&self) -> i32 {
+        self.map.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.map.height()
+    }
+
+    pub fn in_bounds(&self, tile: Tile) -> bool {
+        self.map.in_bounds(tile)
+    }
+
+    /// Whether units have to stop when they step next to an enemy.
+    pub fn zone_of_control(&self) -> bool {
+        self.zone_of_control
+    }
+
+    pub fn set_zone_of_control(&mut self, zone_of_control: bool) {
+        self.zone_of_control = zone_of_control;
+    }
+
+    /// Whether units, as well as walls, get in the way of ranged attacks.
+    pub fn units_block_sight(&self) -> bool {
+        self.units_block_sight
+    }
+
+    pub fn set_units_block_sight(&mut self, units_block_sight: bool) {
+        self.units_block_sight = units_block_sight;
+    }
+
+    /// Whether each side only sees what its units have in view.
+    pub fn fog_of_war(&self) -> bool {
+        self.fog_of_war
+    }
+
+    pub fn set_fog_of_war(&mut self, fog_of_war: bool) {
+        self.fog_of_war = fog_of_war;
+    }
+
+    /// Tiles `side` can see; every tile when there's no fog of war.
+    pub fn visible_tiles(&self, side: Side) -> HashSet<Tile> {
+        if self.fog_of_war {
+            fog::visible_tiles(self, side)
+        } else {
+            self.map.tiles().into_iter().collect()
+        }
+    }
+
+    pub fn current_turn(&self) -> Side {
+        self.current_turn
+    }
+
+    pub fn turn_number(&self) -> u32 {
+        self.turn_number
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&mut self) -> &mut XorShiftRng {
+        &mut self.rng
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = rng_from_seed(seed);
+    }
+
+    pub fn set_turn(&mut self, side: Side) {
+        self.current_turn = side;
+        self.turn_states.clear();
+    }
+
+    /// Ends the current side's turn, running the end of turn hook of its
+    /// status effects and then the start of turn hook of the next side's.
+    pub fn end_turn(&mut self) -> Vec<BattleEvent> {
+        let (side, turn) = (self.current_turn, self.turn_number);
+        let mut events = self.pass_turn();
+        if let Some((winner, objective)) = self.outcome() {
+            events.push(BattleEvent::BattleOver { winner, objective });
+        }
+        self.publish_all(side, turn, &events);
+        events
+    }
+
+    fn pass_turn(&mut self) -> Vec<BattleEvent> {
+        let side = self.current_turn;
+        let mut events = Vec::new();
+        for unit_id in self.units.ids_of(side) {
+            let used = self.turn_states.get(&unit_id).and_then(|state| state.ability);
+            let unit = self.units.get_unit_mut(unit_id).unwrap();
+            unit.tick_cooldowns();
+            if let Some(index) = used {
+                unit.start_cooldown(index);
+            }
+            let expired = unit.get_effects_mut().end_of_turn();
+            for kind in expired {
+                events.push(BattleEvent::EffectExpired { unit: unit_id, kind });
+            }
+        }
+        self.set_turn(side.opponent());
+        if self.current_turn == Side::Player {
+            self.turn_number += 1;
+        }
+        events.push(BattleEvent::TurnEnded {
+            side,
+            next: self.current_turn,
+        });
+        for unit_id in self.units.ids_of(self.current_turn) {
+            events.extend(self.start_unit_turn(unit_id));
+        }
+        events
+    }
+
+    fn start_unit_turn(&mut self, unit_id: UnitId) -> Vec<BattleEvent> {
+        let mut events = Vec::new();
+        let ticks = match self.units.get_unit(unit_id) {
+            Some(unit) => unit.get_effects().start_of_turn(),
+            None => return events,
+        };
+        for (kind, tick) in ticks {
+            let (change, remaining, dead) = {
+                let health = self.units.get_unit_mut(unit_id).unwrap().get_health_mut();
+                let change = match tick {
+                    Tick::Damage(damage) => -(health.take_hit(damage, DamageType::Magic).dealt as i16),
+                    Tick::Heal(amount) => health.heal(amount) as i16,
+                };
+                (change, health.get_current(), health.is_dead())
+            };
+            events.push(BattleEvent::EffectTicked { unit: unit_id, kind, change, remaining });
+            if dead {
+                events.push(self.kill(unit_id));
+                break;
+            }
+        }
+        events
+    }
+
+    pub fn add_effect(&mut self, unit_id: UnitId, effect: StatusEffect) -> Result<BattleEvent, RuleError> {
+        let (side, turn) = (self.current_turn, self.turn_number);
+        let unit = self.units.get_unit_mut(unit_id).ok_or(RuleError::NoSuchUnit(unit_id))?;
+        unit.get_effects_mut().add(effect);
+        let event = BattleEvent::EffectApplied { unit: unit_id, effect };
+        self.publish(side, turn, event.clone());
+        Ok(event)
+    }
+
+    /// Identifies the position for search caches: units, side to move and who has acted.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.units.hash_state(&mut hasher);
+        self.current_turn.hash(&mut hasher);
+        for corpse in &self.corpses {
+            corpse.get_tile().hash(&mut hasher);
+        }
+        let mut turn_states: Vec<(&UnitId, &TurnState)> = self.turn_states.iter().collect();
+        turn_states.sort_by_key(|&(id, _)| *id);
+        turn_states.hash(&mut hasher);
+        if self.has_turn_limit() {
+            self.turn_number.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// The side that has met its objectives, or left the other with no units.
+    pub fn winner(&self) -> Option<Side> {
+        self.outcome().map(|(side, _)| side)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    fn is_stunned(&self, unit_id: UnitId) -> bool {
+        self.units
+            .get_unit(unit_id)
+            .map(|unit| unit.get_effects().prevents_acting())
+            .unwrap_or(false)
+    }
+
+    pub fn can_move(&self, unit_id: UnitId) -> bool {
+        let state = self.turn_states.get(&unit_id).cloned().unwrap_or_default();
+        !state.moved && !state.attacked && !self.is_stunned(unit_id)
+    }
+
+    pub fn can_attack(&self, unit_id: UnitId) -> bool {
+        let state = self.turn_states.get(&unit_id).cloned().unwrap_or_default();
+        !state.attacked && !self.is_stunned(unit_id)
+    }
+
+    /// Tiles the unit could end its move on this turn.
+    pub fn reachable_tiles(&self, unit_id: UnitId) -> HashSet<Tile> {
+        match Pathfinder::new(self, unit_id) {
+            Some(pathfinder) => pathfinder.reachable().into_iter().map(|(tile, _)| tile).collect(),
+            None => HashSet::new(),
+        }
+    }
+
+    /// The cheapest route the unit could take to `to` this turn.
+    pub fn path_to(&self, unit_id: UnitId, to: Tile) -> Option<Path> {
+        Pathfinder::new(self, unit_id).and_then(|pathfinder| pathfinder.find_path(to))
+    }
+
+    /// Tiles covered by the unit's attack pattern from `from`, with whether
+    /// each one is in line of sight. Adjacent tiles are always in sight.
+    fn pattern_tiles_from(&self, unit_id: UnitId, from: Tile) -> Vec<(Tile, bool)> {
+        match self.units.get_unit(unit_id) {
+            Some(unit) => unit.get_attack_pattern()
+                .iter()
+                .map(|offset| from + *offset)
+                .filter(|tile| self.in_bounds(*tile))
+                .map(|tile| {
+                    let visible = from.distance(tile) <= 1 || has_line_of_sight(self, from, tile, Some(unit_id));
+                    (tile, visible)
+                })
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Tiles the unit could hit from `from`, ignoring whether anything stands there.
+    pub fn attack_tiles_from(&self, unit_id: UnitId, from: Tile) -> Vec<Tile> {
+        self.pattern_tiles_from(unit_id, from)
+            .into_iter()
+            .filter_map(|(tile, visible)| if visible { Some(tile) } else { None })
+            .collect()
+    }
+
+    /// Tiles in the unit's attack pattern from `from` that are out of sight.
+    pub fn blocked_attack_tiles_from(&self, unit_id: UnitId, from: Tile) -> Vec<Tile> {
+        self.pattern_tiles_from(unit_id, from)
+            .into_iter()
+            .filter_map(|(tile, visible)| if visible { None } else { Some(tile) })
+            .collect()
+    }
+
+    pub fn attack_targets(&self, unit_id: UnitId) -> Vec<UnitId> {
+        let unit = match self.units.get_unit(unit_id) {
+            Some(unit) => unit,
+            None => return Vec::new(),
+        };
+        let mut targets: Vec<UnitId> = self.attack_tiles_from(unit_id, unit.get_tile())
+            .into_iter()
+            .filter_map(|tile| self.units.unit_at(tile))
+            .filter(|target| {
+                self.units.get_unit(*target).map(|t| t.get_side()) == Some(unit.get_side().opponent())
+            })
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    /// Every command the side to move could legally issue right now.
+    pub fn legal_commands(&self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        if self.is_over() {
+            return commands;
+        }
+        for unit_id in self.units.ids_of(self.current_turn) {
+            if self.can_attack(unit_id) {
+                for target in self.attack_targets(unit_id) {
+                    commands.push(Command::Attack { unit: unit_id, target });
+                }
+                for ability in self.ready_abilities(unit_id) {
+                    for target in self.ability_targets(unit_id, ability) {
+                        commands.push(Command::UseAbility { unit: unit_id, ability, target });
+                    }
+                }
+            }
+            if self.can_move(unit_id) {
+                let mut tiles: Vec<Tile> = self.reachable_tiles(unit_id).into_iter().collect();
+                tiles.sort_by_key(|tile| (tile.x(), tile.y()));
+                for to in tiles {
+                    commands.push(Command::Move { unit: unit_id, to });
+                }
+            }
+        }
+        commands.push(Command::EndTurn);
+        commands
+    }
+
+    fn acting_unit(&self, unit_id: UnitId) -> Result<&Unit, RuleError> {
+        let unit = self.units.get_unit(unit_id).ok_or(RuleError::NoSuchUnit(unit_id))?;
+        if unit.get_side() != self.current_turn {
+            return Err(RuleError::NotYourTurn(unit_id));
+        }
+        if unit.get_effects().prevents_acting() {
+            return Err(RuleError::Stunned(unit_id));
+        }
+        Ok(unit)
+    }
+
+    pub fn apply(&mut self, command: Command) -> Result<Vec<BattleEvent>, RuleError> {
+        if self.is_over() {
+            return Err(RuleError::BattleOver);
+        }
+        let (side, turn) = (self.current_turn, self.turn_number);
+        let mut events = match command {
+            Command::Move { unit, to } => self.move_unit(unit, to),
+            Command::Attack { unit, target } => self.attack(unit, target),
+            Command::UseAbility { unit, ability, target } => self.use_ability(unit, ability, target),
+            Command::EndTurn => Ok(self.pass_turn()),
+        }?;
+        if let Some((winner, objective)) = self.outcome() {
+            events.push(BattleEvent::BattleOver { winner, objective });
+        }
+        self.publish_all(side, turn, &events);
+        Ok(events)
+    }
+
+    fn move_unit(&mut self, unit_id: UnitId, to: Tile) -> Result<Vec<BattleEvent>, RuleError> {
+        let from = self.acting_unit(unit_id)?.get_tile();
+        if !self.can_move(unit_id) {
+            return Err(RuleError::AlreadyMoved(unit_id));
+        }
+        let path = self.path_to(unit_id, to).ok_or(RuleError::Unreachable(to))?;
+        self.units.get_unit_mut(unit_id).unwrap().set_tile(to);
+        self.turn_states.entry(unit_id).or_insert_with(TurnState::default).moved = true;
+        Ok(vec![BattleEvent::UnitMoved {
+            unit: unit_id,
+            from,
+            to,
+            path: path.tiles,
+        }])
+    }
+
+    fn attack(&mut self, unit_id: UnitId, target_id: UnitId) -> Result<Vec<BattleEvent>, RuleError> {
+        let (side, damage, damage_type) = {
+            let unit = self.acting_unit(unit_id)?;
+            (unit.get_side(), unit.get_damage(), unit.get_damage_type())
+        };
+        if !self.can_attack(unit_id) {
+            return Err(RuleError::AlreadyAttacked(unit_id));
+        }
+        let target_side = self.units
+            .get_unit(target_id)
+            .ok_or(RuleError::NoSuchUnit(target_id))?
+            .get_side();
+        if target_side == side {
+            return Err(RuleError::FriendlyTarget(target_id));
+        }
+        if !self.attack_targets(unit_id).contains(&target_id) {
+            return Err(RuleError::OutOfRange(target_id));
+        }
+        self.turn_states.entry(unit_id).or_insert_with(TurnState::default).attacked = true;
+        let report = self.units.get_unit_mut(target_id).unwrap().receive_hit(damage, damage_type);
+        let mut events = vec![BattleEvent::UnitDamaged {
+            attacker: unit_id,
+            target: target_id,
+            report,
+        }];
+        if report.killed {
+            events.push(self.kill(target_id));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A player warrior at 1,1 next to an enemy warrior at 2,1.
+    fn skirmish() -> (Battle, UnitId, UnitId) {
+        let mut battle = Battle::new(7);
+        let player = battle.units_mut().make_unit(Unit::warrior(Tile::new(1, 1), Side::Player));
+        let enemy = battle.units_mut().make_unit(Unit::warrior(Tile::new(2, 1), Side::Enemy));
+        (battle, player, enemy)
+    }
+
+    #[test]
+    fn parses_unit_ids_past_i16() {
+        let command = Command::parse("attack 40000 70000").unwrap();
+        assert_eq!(command, Command::Attack { unit: UnitId::new(40000), target: UnitId::new(70000) });
+        assert_eq!(Command::parse(&command.to_string()), Ok(command));
+        assert!(Command::parse("move -1 2 3").is_err());
+    }
+
+    #[test]
+    fn moves_once_per_turn() {
+        let (mut battle, player, _) = skirmish();
+        let events = battle.apply(Command::Move { unit: player, to: Tile::new(1, 3) }).unwrap();
+        match events[0] {
+            BattleEvent::UnitMoved { unit, from, to, .. } => {
+                assert_eq!((unit, from, to), (player, Tile::new(1, 1), Tile::new(1, 3)))
+            }
+            ref event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(battle.units().get_unit(player).unwrap().get_tile(), Tile::new(1, 3));
+        assert_eq!(
+            battle.apply(Command::Move { unit: player, to: Tile::new(1, 2) }),
+            Err(RuleError::AlreadyMoved(player))
+        );
+        assert_eq!(
+            battle.apply(Command::Move { unit: player, to: Tile::new(9, 9) }),
+            Err(RuleError::AlreadyMoved(player))
+        );
+    }
+
+    #[test]
+    fn only_the_side_to_move_acts() {
+        let (mut battle, _, enemy) = skirmish();
+        assert_eq!(
+            battle.apply(Command::Move { unit: enemy, to: Tile::new(3, 1) }),
+            Err(RuleError::NotYourTurn(enemy))
+        );
+    }
+
+    #[test]
+    fn attacks_deal_damage_once_per_turn() {
+        let (mut battle, player, enemy) = skirmish();
+        let before = battle.units().get_unit(enemy).unwrap().get_health().get_current();
+        let events = battle.apply(Command::Attack { unit: player, target: enemy }).unwrap();
+        let report = match events[0] {
+            BattleEvent::UnitDamaged { report, .. } => report,
+            ref event => panic!("unexpected event {:?}", event),
+        };
+        assert!(report.dealt > 0);
+        assert_eq!(report.remaining, before - report.dealt as i16);
+        assert_eq!(battle.units().get_unit(enemy).unwrap().get_health().get_current(), report.remaining);
+        assert_eq!(
+            battle.apply(Command::Attack { unit: player, target: enemy }),
+            Err(RuleError::AlreadyAttacked(player))
+        );
+        assert!(!battle.can_move(player));
+    }
+
+    #[test]
+    fn attacks_need_an_enemy_in_range() {
+        let (mut battle, player, enemy) = skirmish();
+        let friend = battle.units_mut().make_unit(Unit::warrior(Tile::new(1, 2), Side::Player));
+        assert_eq!(
+            battle.apply(Command::Attack { unit: player, target: friend }),
+            Err(RuleError::FriendlyTarget(friend))
+        );
+        battle.units_mut().get_unit_mut(enemy).unwrap().set_tile(Tile::new(8, 8));
+        assert_eq!(
+            battle.apply(Command::Attack { unit: player, target: enemy }),
+            Err(RuleError::OutOfRange(enemy))
+        );
+    }
+
+    #[test]
+    fn end_turn_passes_to_the_other_side() {
+        let (mut battle, player, _) = skirmish();
+        battle.apply(Command::Move { unit: player, to: Tile::new(1, 3) }).unwrap();
+        let events = battle.apply(Command::EndTurn).unwrap();
+        assert!(events.contains(&BattleEvent::TurnEnded { side: Side::Player, next: Side::Enemy }));
+        assert_eq!(battle.current_turn(), Side::Enemy);
+        assert_eq!(battle.turn_number(), 1);
+
+        battle.apply(Command::EndTurn).unwrap();
+        assert_eq!(battle.current_turn(), Side::Player);
+        assert_eq!(battle.turn_number(), 2);
+        assert!(battle.can_move(player));
+    }
+
+    #[test]
+    fn end_turn_ends_the_battle_when_a_tick_kills_the_last_unit() {
+        let (mut battle, _, enemy) = skirmish();
+        battle.units_mut().get_unit_mut(enemy).unwrap().get_health_mut().take_damage(9);
+        battle.add_effect(enemy, StatusEffect::new(EffectKind::Poison, 2, 2)).unwrap();
+        let events = battle.end_turn();
+        assert!(events.contains(&BattleEvent::UnitDied { unit: enemy }));
+        match events.last() {
+            Some(&BattleEvent::BattleOver { winner, .. }) => assert_eq!(winner, Side::Player),
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert!(battle.is_over());
+    }
+
+    #[test]
+    fn queues_everything_that_happens_once() {
+        let (mut battle, player, enemy) = skirmish();
+        let moved = battle.apply(Command::Move { unit: player, to: Tile::new(1, 2) }).unwrap();
+        let ended = battle.end_turn();
+        let slain = battle.slay(player).unwrap();
+        let published = |side, events: &Vec<BattleEvent>| -> Vec<GameEvent> {
+            events.iter().map(|event| GameEvent::Battle { side, turn: 1, event: event.clone() }).collect()
+        };
+        let mut expected = published(Side::Player, &moved);
+        expected.extend(published(Side::Player, &ended));
+        expected.extend(published(Side::Enemy, &slain));
+        assert_eq!(battle.take_events(), expected);
+        let objective = Objective { side: Side::Enemy, condition: Condition::Eliminate };
+        assert_eq!(slain.last(), Some(&BattleEvent::BattleOver { winner: Side::Enemy, objective }));
+        assert!(battle.take_events().is_empty());
+        assert_eq!(battle.slay(player), Err(RuleError::NoSuchUnit(player)));
+        assert!(battle.units().get_unit(enemy).is_some());
+
+        let mut what_if = battle.clone();
+        what_if.end_turn();
+        assert!(what_if.take_events().is_empty());
+    }
+
+    #[test]
+    fn cooldowns_skip_the_turn_of_use() {
+        let (mut battle, _, enemy) = skirmish();
+        battle.units_mut().get_unit_mut(enemy).unwrap().set_tile(Tile::new(8, 8));
+        let monk = battle.units_mut().make_unit(Unit::monk(Tile::new(1, 3), Side::Player));
+        let heal = Command::UseAbility { unit: monk, ability: 0, target: Tile::new(1, 1) };
+        battle.apply(heal).unwrap();
+        battle.apply(Command::EndTurn).unwrap();
+        battle.apply(Command::EndTurn).unwrap();
+        assert_eq!(battle.apply(heal), Err(RuleError::OnCooldown(monk, 0)));
+        battle.apply(Command::EndTurn).unwrap();
+        battle.apply(Command::EndTurn).unwrap();
+        assert!(battle.apply(heal).is_ok());
+    }
+
+    #[test]
+    fn last_side_standing_wins() {
+        let (mut battle, player, enemy) = skirmish();
+        assert_eq!(battle.winner(), None);
+        battle.units_mut().get_unit_mut(enemy).unwrap().get_health_mut().take_damage(9);
+        let events = battle.apply(Command::Attack { unit: player, target: enemy }).unwrap();
+        assert!(events.contains(&BattleEvent::UnitDied { unit: enemy }));
+        assert!(battle.units().get_unit(enemy).is_none());
+        assert_eq!(battle.winner(), Some(Side::Player));
+        match events.last() {
+            Some(&BattleEvent::BattleOver { winner, .. }) => assert_eq!(winner, Side::Player),
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert_eq!(battle.apply(Command::EndTurn), Err(RuleError::BattleOver));
+    }
+}
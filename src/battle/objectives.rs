@@ -0,0 +1,135 @@
+use std::fmt;
+use battle::Battle;
+use game_object::*;
+
+/// Something a side is fighting for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Condition {
+    /// Win once the other side has no units left.
+    Eliminate,
+    /// Win once the other side has no king left.
+    KillKing,
+    /// Lose once there's no unit of this class left on the side.
+    Protect(UnitClass),
+    /// Win once this many turns have passed.
+    Survive(u32),
+    /// Win once a unit of the side stands on the tile.
+    Reach(Tile),
+}
+
+impl Condition {
+    /// Parses the words of a scenario's `objective` directive after the side.
+    pub fn parse(words: &[&str]) -> Result<Condition, String> {
+        let number = |index: usize| -> Result<i16, String> {
+            words.get(index)
+                .and_then(|word| word.parse().ok())
+                .ok_or_else(|| "expected a number".to_owned())
+        };
+        match words.first() {
+            Some(&"eliminate") => Ok(Condition::Eliminate),
+            Some(&"kill-king") => Ok(Condition::KillKing),
+            Some(&"protect") => words.get(1)
+                .and_then(|word| UnitClass::from_name(word))
+                .map(Condition::Protect)
+                .ok_or_else(|| "expected a unit class".to_owned()),
+            Some(&"survive") => match words.get(1).and_then(|word| word.parse::<u32>().ok()) {
+                Some(0) => Err("can't survive zero turns".to_owned()),
+                Some(turns) => Ok(Condition::Survive(turns)),
+                None => Err("expected a number of turns".to_owned()),
+            },
+            Some(&"reach") => Ok(Condition::Reach(Tile::new(number(1)?, number(2)?))),
+            _ => Err("expected an objective".to_owned()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Objective {
+    pub side: Side,
+    pub condition: Condition,
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let opponent = self.side.opponent().name();
+        match self.condition {
+            Condition::Eliminate => write!(f, "defeat every {} unit", opponent),
+            Condition::KillKing => write!(f, "kill the {} king", opponent),
+            Condition::Protect(class) => write!(f, "keep a {} alive", class.name()),
+            Condition::Survive(turns) => write!(f, "survive {} turns", turns),
+            Condition::Reach(tile) => write!(f, "reach {},{}", tile.x(), tile.y()),
+        }
+    }
+}
+
+impl Battle {
+    pub fn objectives(&self) -> &[Objective] {
+        &self.objectives
+    }
+
+    /// Replaces what the sides are fighting for. Eliminating the other
+    /// side always wins, whatever else is declared.
+    pub fn set_objectives(&mut self, objectives: Vec<Objective>) {
+        self.objectives = objectives;
+    }
+
+    /// The side that has won and the objective that decided it, checking
+    /// elimination first and then the declared objectives in order.
+    pub fn outcome(&self) -> Option<(Side, Objective)> {
+        let eliminated = |side: Side| {
+            let objective = Objective {
+                side,
+                condition: Condition::Eliminate,
+            };
+            Some((side, objective))
+        };
+        let player_alive = !self.units.ids_of(Side::Player).is_empty();
+        let enemy_alive = !self.units.ids_of(Side::Enemy).is_empty();
+        match (player_alive, enemy_alive) {
+            (true, false) => return eliminated(Side::Player),
+            (false, true) => return eliminated(Side::Enemy),
+            (false, false) => return None,
+            (true, true) => (),
+        }
+        self.objectives
+            .iter()
+            .filter_map(|objective| self.decide(*objective).map(|winner| (winner, *objective)))
+            .next()
+    }
+
+    /// Who, if anyone, the objective makes the winner right now.
+    fn decide(&self, objective: Objective) -> Option<Side> {
+        let side = objective.side;
+        let has_class = |side: Side, class: UnitClass| {
+            self.units
+                .ids_of(side)
+                .into_iter()
+                .filter_map(|id| self.units.get_unit(id))
+                .any(|unit| unit.get_class() == class)
+        };
+        let met = match objective.condition {
+            Condition::Eliminate => self.units.ids_of(side.opponent()).is_empty(),
+            Condition::KillKing => !has_class(side.opponent(), UnitClass::King),
+            Condition::Protect(class) => return if has_class(side, class) { None } else { Some(side.opponent()) },
+            Condition::Survive(turns) => self.turn_number > turns,
+            Condition::Reach(tile) => self.units
+                .unit_at(tile)
+                .and_then(|id| self.units.get_unit(id))
+                .map(|unit| unit.get_side() == side)
+                .unwrap_or(false),
+        };
+        if met {
+            Some(side)
+        } else {
+            None
+        }
+    }
+
+    /// Whether how far the battle has gone can decide it.
+    pub(super) fn has_turn_limit(&self) -> bool {
+        self.objectives.iter().any(|objective| match objective.condition {
+            Condition::Survive(_) => true,
+            _ => false,
+        })
+    }
+}
@@ -0,0 +1,156 @@
+extern crate oga_summer_2018;
+#[macro_use]
+extern crate serde_json;
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::process;
+use oga_summer_2018::ai::profile::scenario_ai;
+use oga_summer_2018::ai::{Ai, GreedyAi, ScriptedAi, Timing};
+use oga_summer_2018::event_bus::EventBus;
+use oga_summer_2018::game_object::Side;
+use oga_summer_2018::scenario::Scenario;
+use oga_summer_2018::simulation::{run_game, GameRecord};
+
+const USAGE: &str = "usage: simulate <scenario> [--seed N] [--games N] [--max-turns N] [--script FILE] [--json]";
+
+struct Options {
+    scenario: String,
+    seed: u64,
+    games: u32,
+    max_turns: u32,
+    script: Option<String>,
+    json: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        scenario: String::new(),
+        seed: 0,
+        games: 1,
+        max_turns: 100,
+        script: None,
+        json: false,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--seed" => options.seed = parse_value(&arg, args.next())?,
+            "--games" => options.games = parse_value(&arg, args.next())?,
+            "--max-turns" => options.max_turns = parse_value(&arg, args.next())?,
+            "--script" => options.script = Some(args.next().ok_or("--script needs a file")?),
+            "--json" => options.json = true,
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => options.scenario = arg,
+        }
+    }
+    if options.scenario.is_empty() {
+        return Err("no scenario given".to_owned());
+    }
+    Ok(options)
+}
+
+fn parse_value<T: ::std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} needs a number", flag))
+}
+
+fn player_ai(options: &Options, seed: u64) -> Result<Box<dyn Ai>, String> {
+    match options.script {
+        Some(ref path) => {
+            let mut script = String::new();
+            File::open(path)
+                .and_then(|mut file| file.read_to_string(&mut script))
+                .map_err(|e| format!("could not read {}: {}", path, e))?;
+            Ok(Box::new(ScriptedAi::parse(&script[..])?))
+        }
+        None => Ok(Box::new(GreedyAi::new(seed))),
+    }
+}
+
+fn winner_name(record: &GameRecord) -> &'static str {
+    record.winner.map(|side| side.name()).unwrap_or("draw")
+}
+
+fn print_text(game: u32, record: &GameRecord) {
+    println!("game {} (seed {})", game, record.seed);
+    for entry in &record.log {
+        println!("turn {} {}: {}", entry.turn, entry.side.name(), entry.event);
+    }
+    println!("winner: {} after {} turns", winner_name(record), record.turns);
+    println!();
+}
+
+fn record_json(record: &GameRecord) -> serde_json::Value {
+    let log: Vec<serde_json::Value> = record
+        .log
+        .iter()
+        .map(|entry| {
+            json!({
+                "turn": entry.turn,
+                "side": entry.side.name(),
+                "event": entry.event.to_string(),
+            })
+        })
+        .collect();
+    json!({
+        "seed": record.seed,
+        "winner": record.winner.map(|side| side.name()),
+        "turns": record.turns,
+        "log": log,
+    })
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let scenario = Scenario::load(&options.scenario[..]).map_err(|e| e.to_string())?;
+    let mut records = Vec::new();
+    let mut bus = EventBus::new();
+    for game in 0..options.games {
+        let seed = options.seed + game as u64;
+        let battle = scenario.to_battle(seed);
+        let mut player = player_ai(options, seed)?;
+        let mut enemy = match scenario_ai(&scenario, Side::Enemy, &battle, seed.wrapping_add(1), Timing::Replayable)? {
+            Some(ai) => ai,
+            None => Box::new(GreedyAi::new(seed.wrapping_add(1))),
+        };
+        let record = run_game(battle, &mut *player, &mut *enemy, options.max_turns, &mut bus);
+        if !options.json {
+            print_text(game + 1, &record);
+        }
+        records.push(record);
+    }
+    let wins = |name: &str| records.iter().filter(|record| winner_name(record) == name).count();
+    if options.json {
+        let games: Vec<serde_json::Value> = records.iter().map(record_json).collect();
+        let output = json!({
+            "scenario": scenario.name,
+            "games": games,
+            "summary": {
+                "player": wins("player"),
+                "enemy": wins("enemy"),
+                "draw": wins("draw"),
+            },
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    } else {
+        println!(
+            "{}: player {} / enemy {} / draw {}",
+            scenario.name,
+            wins("player"),
+            wins("enemy"),
+            wins("draw")
+        );
+    }
+    Ok(())
+}
+
+fn main() {
+    let result = parse_options().and_then(|options| run(&options));
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,218 @@
+extern crate oga_summer_2018;
+extern crate rand;
+
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::process;
+use oga_summer_2018::ai;
+use oga_summer_2018::battle::rng_from_seed;
+use oga_summer_2018::event_bus::EventBus;
+use oga_summer_2018::game_object::{Side, UnitClass};
+use oga_summer_2018::scenario::Scenario;
+use oga_summer_2018::simulation::run_game;
+use oga_summer_2018::statistics::ClassTable;
+use rand::Rng;
+
+const USAGE: &str = "usage: tournament <scenario>... [--games N] [--seed N] [--max-turns N] \
+                     [--player AI] [--enemy AI] [--fixed] [--csv FILE] [--markdown FILE]";
+
+struct Options {
+    scenarios: Vec<String>,
+    games: u32,
+    seed: u64,
+    max_turns: u32,
+    player: String,
+    enemy: String,
+    randomize: bool,
+    csv: Option<String>,
+    markdown: Option<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        scenarios: Vec::new(),
+        games: 1000,
+        seed: 0,
+        max_turns: 100,
+        player: "greedy".to_owned(),
+        enemy: "greedy".to_owned(),
+        randomize: true,
+        csv: None,
+        markdown: None,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--games" => options.games = parse_value(&arg, args.next())?,
+            "--seed" => options.seed = parse_value(&arg, args.next())?,
+            "--max-turns" => options.max_turns = parse_value(&arg, args.next())?,
+            "--player" => options.player = args.next().ok_or("--player needs an AI name")?,
+            "--enemy" => options.enemy = args.next().ok_or("--enemy needs an AI name")?,
+            "--fixed" => options.randomize = false,
+            "--csv" => options.csv = Some(args.next().ok_or("--csv needs a file")?),
+            "--markdown" => options.markdown = Some(args.next().ok_or("--markdown needs a file")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => options.scenarios.push(arg),
+        }
+    }
+    if options.scenarios.is_empty() {
+        return Err("no scenarios given".to_owned());
+    }
+    for name in &[&options.player, &options.enemy] {
+        if ai::by_name(name, 0).is_none() {
+            return Err(format!("unknown AI '{}', expected one of {}", name, ai::names().join(", ")));
+        }
+    }
+    Ok(options)
+}
+
+fn parse_value<T: ::std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} needs a number", flag))
+}
+
+/// Keeps the scenario's positions but rolls a new class for every unit.
+fn random_army(scenario: &Scenario, seed: u64) -> Scenario {
+    let mut rng = rng_from_seed(seed);
+    let classes = UnitClass::all();
+    let mut army = scenario.clone();
+    for placement in army.units.iter_mut() {
+        placement.class = *rng.choose(&classes[..]).unwrap();
+    }
+    army
+}
+
+struct ScenarioResult {
+    name: String,
+    games: u32,
+    player_wins: u32,
+    enemy_wins: u32,
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut table = ClassTable::default();
+    let mut results = Vec::new();
+    let mut bus = EventBus::new();
+    for name in &options.scenarios {
+        let scenario = Scenario::load(name).map_err(|e| format!("{}: {}", name, e))?;
+        let mut result = ScenarioResult {
+            name: scenario.name.clone(),
+            games: options.games,
+            player_wins: 0,
+            enemy_wins: 0,
+        };
+        for game in 0..options.games {
+            let seed = options.seed.wrapping_add(game as u64);
+            let army = if options.randomize { random_army(&scenario, seed) } else { scenario.clone() };
+            let mut player = ai::by_name(&options.player, seed).unwrap();
+            let mut enemy = ai::by_name(&options.enemy, seed.wrapping_add(1)).unwrap();
+            let record = run_game(army.to_battle(seed), &mut *player, &mut *enemy, options.max_turns, &mut bus);
+            match record.winner {
+                Some(Side::Player) => result.player_wins += 1,
+                Some(Side::Enemy) => result.enemy_wins += 1,
+                None => (),
+            }
+            table.add_game(&record);
+        }
+        results.push(result);
+    }
+
+    let markdown = markdown_report(&table, &results);
+    match options.markdown {
+        Some(ref path) => write_file(path, &markdown)?,
+        None => print!("{}", markdown),
+    }
+    if let Some(ref path) = options.csv {
+        write_file(path, &csv_report(&table))?;
+    }
+    Ok(())
+}
+
+fn write_file(path: &str, contents: &str) -> Result<(), String> {
+    File::create(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| format!("could not write {}: {}", path, e))
+}
+
+const COLUMNS: [&str; 10] = [
+    "class",
+    "units",
+    "wins",
+    "win_rate",
+    "ci_low",
+    "ci_high",
+    "contribution",
+    "avg_damage_dealt",
+    "avg_damage_taken",
+    "avg_survival_turns",
+];
+
+fn class_rows(table: &ClassTable) -> Vec<Vec<String>> {
+    UnitClass::all()
+        .into_iter()
+        .map(|class| {
+            let stats = table.get(class);
+            let (low, high) = stats.confidence_interval();
+            vec![
+                class.name().to_owned(),
+                stats.units.to_string(),
+                stats.wins.to_string(),
+                format!("{:.3}", stats.win_rate()),
+                format!("{:.3}", low),
+                format!("{:.3}", high),
+                format!("{:+.3}", table.contribution(class)),
+                format!("{:.2}", stats.avg_damage_dealt()),
+                format!("{:.2}", stats.avg_damage_taken()),
+                format!("{:.2}", stats.avg_survived_turns()),
+            ]
+        })
+        .collect()
+}
+
+fn csv_report(table: &ClassTable) -> String {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    for row in class_rows(table) {
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn markdown_report(table: &ClassTable, results: &[ScenarioResult]) -> String {
+    let mut markdown = String::from("## Scenarios\n\n");
+    markdown.push_str("| scenario | games | player wins | enemy wins | draws |\n");
+    markdown.push_str("|---|---|---|---|---|\n");
+    for result in results {
+        markdown.push_str(&markdown_row(&[
+            result.name.clone(),
+            result.games.to_string(),
+            result.player_wins.to_string(),
+            result.enemy_wins.to_string(),
+            (result.games - result.player_wins - result.enemy_wins).to_string(),
+        ]));
+    }
+    markdown.push_str("\n## Classes\n\n");
+    let header: Vec<String> = COLUMNS.iter().map(|column| column.to_string()).collect();
+    markdown.push_str(&markdown_row(&header));
+    markdown.push_str(&markdown_row(&vec!["---".to_owned(); COLUMNS.len()]));
+    for row in class_rows(table) {
+        markdown.push_str(&markdown_row(&row));
+    }
+    markdown
+}
+
+fn main() {
+    let result = parse_options().and_then(|options| run(&options));
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,413 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use game_object::*;
+use roster::{parse_veteran, Progress, Roster};
+use scenario::{Scenario, ScenarioError};
+use utils::find_resource;
+
+const CAMPAIGN_DIR: &str = "campaigns";
+const CAMPAIGN_EXTENSION: &str = "txt";
+pub const DEFAULT_CAMPAIGN: &str = "long_night";
+/// Souls it costs to raise a new recruit between battles.
+pub const RECRUIT_COST: u32 = 10;
+
+#[derive(Debug)]
+pub enum CampaignError {
+    NotFound(String),
+    Io(io::Error),
+    Parse { line: usize, message: String },
+    Scenario(ScenarioError),
+}
+
+impl fmt::Display for CampaignError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CampaignError::NotFound(ref name) => write!(f, "campaign '{}' not found", name),
+            CampaignError::Io(ref e) => write!(f, "could not read campaign: {}", e),
+            CampaignError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+            CampaignError::Scenario(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for CampaignError {
+    fn description(&self) -> &str {
+        "invalid campaign"
+    }
+}
+
+impl From<io::Error> for CampaignError {
+    fn from(e: io::Error) -> Self {
+        CampaignError::Io(e)
+    }
+}
+
+impl From<ScenarioError> for CampaignError {
+    fn from(e: ScenarioError) -> Self {
+        CampaignError::Scenario(e)
+    }
+}
+
+/// A battle on the campaign map.
+#[derive(Clone, Debug)]
+pub struct CampaignNode {
+    pub id: String,
+    pub scenario: String,
+    /// Where it's drawn on the node map, in grid cells.
+    pub position: (i32, i32),
+    /// Souls earned the first time it's won.
+    pub reward: u32,
+}
+
+/// A graph of battles where winning or losing one decides which can be
+/// fought next, read from a plain text file:
+///
+/// ```text
+/// name The Long Night
+/// node crossing skirmish 0 1 10
+/// node keep royal_guard 1 1 25
+/// win crossing keep
+/// lose keep crossing
+/// ```
+///
+/// The first node is where the campaign starts. Winning a node with no
+/// `win` links wins the campaign; losing one with no `lose` links means
+/// fighting it again.
+#[derive(Clone, Debug)]
+pub struct Campaign {
+    pub id: String,
+    pub name: String,
+    pub nodes: Vec<CampaignNode>,
+    wins: Vec<(String, String)>,
+    losses: Vec<(String, String)>,
+}
+
+impl Campaign {
+    pub fn parse(id: &str, text: &str) -> Result<Campaign, CampaignError> {
+        let mut campaign = Campaign {
+            id: id.to_owned(),
+            name: id.to_owned(),
+            nodes: Vec::new(),
+            wins: Vec::new(),
+            losses: Vec::new(),
+        };
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            campaign
+                .parse_directive(&words)
+                .map_err(|message| CampaignError::Parse { line: index + 1, message })?;
+        }
+        if campaign.nodes.is_empty() {
+            return Err(CampaignError::Parse { line: 0, message: "no nodes".to_owned() });
+        }
+        Ok(campaign)
+    }
+
+    fn parse_directive(&mut self, words: &[&str]) -> Result<(), String> {
+        match words[0] {
+            "name" => self.name = words[1..].join(" "),
+            "node" => {
+                let id = words.get(1).ok_or_else(|| "expected a node name".to_owned())?;
+                let scenario = words.get(2).ok_or_else(|| "expected a scenario".to_owned())?;
+                self.nodes.push(CampaignNode {
+                    id: id.to_string(),
+                    scenario: scenario.to_string(),
+                    position: (parse_number(words.get(3))?, parse_number(words.get(4))?),
+                    reward: if words.len() > 5 { parse_number(words.get(5))? } else { 0 },
+                });
+            }
+            "win" | "lose" => {
+                let from = words.get(1).ok_or_else(|| "expected a node name".to_owned())?;
+                if self.node(from).is_none() {
+                    return Err(format!("unknown node '{}'", from));
+                }
+                for to in &words[2..] {
+                    if self.node(to).is_none() {
+                        return Err(format!("unknown node '{}'", to));
+                    }
+                    let link = (from.to_string(), to.to_string());
+                    if words[0] == "win" {
+                        self.wins.push(link);
+                    } else {
+                        self.losses.push(link);
+                    }
+                }
+            }
+            directive => return Err(format!("unknown directive '{}'", directive)),
+        }
+        Ok(())
+    }
+
+    /// Loads a campaign by file path, or by name from the resources directory.
+    pub fn load(name: &str) -> Result<Campaign, CampaignError> {
+        let path = find(name).ok_or_else(|| CampaignError::NotFound(name.to_owned()))?;
+        let mut text = String::new();
+        File::open(&path)?.read_to_string(&mut text)?;
+        let stem = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(name)
+            .to_owned();
+        Campaign::parse(&stem[..], &text[..])
+    }
+
+    pub fn start(&self) -> &CampaignNode {
+        &self.nodes[0]
+    }
+
+    pub fn node(&self, id: &str) -> Option<&CampaignNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Nodes unlocked by winning or losing `id`.
+    pub fn next(&self, id: &str, won: bool) -> Vec<&str> {
+        let links = if won { &self.wins } else { &self.losses };
+        links
+            .iter()
+            .filter(|&&(ref from, _)| from == id)
+            .map(|&(_, ref to)| &to[..])
+            .collect()
+    }
+
+    /// Every link as `(from, to, won)`, for drawing.
+    pub fn links(&self) -> Vec<(&str, &str, bool)> {
+        let wins = self.wins.iter().map(|&(ref from, ref to)| (&from[..], &to[..], true));
+        let losses = self.losses.iter().map(|&(ref from, ref to)| (&from[..], &to[..], false));
+        wins.chain(losses).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CampaignStatus {
+    InProgress,
+    Won,
+    Lost,
+}
+
+/// Progress through a campaign, kept in a save slot between sessions:
+///
+/// ```text
+/// campaign long_night
+/// souls 10
+/// available ruins raid
+/// completed crossing
+/// veteran Mortis warrior 2 7 1
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CampaignSave {
+    pub campaign: String,
+    pub souls: u32,
+    /// Nodes that can be fought next.
+    pub available: Vec<String>,
+    pub completed: Vec<String>,
+    pub roster: Roster,
+}
+
+impl CampaignSave {
+    /// A fresh start, with the army of the first battle.
+    pub fn new(campaign: &Campaign) -> Result<CampaignSave, CampaignError> {
+        let scenario = Scenario::load(&campaign.start().scenario[..])?;
+        Ok(CampaignSave {
+            campaign: campaign.id.clone(),
+            souls: 0,
+            available: vec![campaign.start().id.clone()],
+            completed: Vec::new(),
+            roster: Roster::from_scenario(&scenario),
+        })
+    }
+
+    /// Lost once every veteran has fallen and there aren't the souls to
+    /// recruit another.
+    pub fn status(&self) -> CampaignStatus {
+        if self.roster.veterans.is_empty() && self.souls < RECRUIT_COST {
+            CampaignStatus::Lost
+        } else if self.available.is_empty() {
+            CampaignStatus::Won
+        } else {
+            CampaignStatus::InProgress
+        }
+    }
+
+    pub fn is_available(&self, id: &str) -> bool {
+        self.available.iter().any(|available| available == id)
+    }
+
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.completed.iter().any(|completed| completed == id)
+    }
+
+    /// Moves the campaign on after a battle at `id`, returning a line for
+    /// each thing that changed.
+    pub fn record_battle(&mut self, campaign: &Campaign, id: &str, won: bool) -> Vec<String> {
+        let mut messages = Vec::new();
+        let next = campaign.next(id, won);
+        if won {
+            if !self.is_completed(id) {
+                let reward = campaign.node(id).map(|node| node.reward).unwrap_or(0);
+                self.souls += reward;
+                self.completed.push(id.to_owned());
+                messages.push(format!("gained {} souls", reward));
+            }
+            self.available = next.iter().map(|next| next.to_string()).collect();
+        } else if !next.is_empty() {
+            self.available = next.iter().map(|next| next.to_string()).collect();
+        }
+        match self.status() {
+            CampaignStatus::Won => messages.push(format!("{} is won", campaign.name)),
+            CampaignStatus::Lost => messages.push(format!("{} is lost", campaign.name)),
+            CampaignStatus::InProgress => (),
+        }
+        messages
+    }
+
+    /// Spends souls on a new level one unit.
+    pub fn recruit(&mut self, class: UnitClass) -> Result<Progress, String> {
+        if self.souls < RECRUIT_COST {
+            return Err(format!("recruiting needs {} souls", RECRUIT_COST));
+        }
+        self.souls -= RECRUIT_COST;
+        self.roster.recruit(class);
+        let name = self.roster.veterans.last().unwrap().name.clone();
+        Ok(Progress::Recruited { name, class })
+    }
+
+    pub fn parse(text: &str) -> Result<CampaignSave, CampaignError> {
+        let mut save = CampaignSave {
+            campaign: DEFAULT_CAMPAIGN.to_owned(),
+            souls: 0,
+            available: Vec::new(),
+            completed: Vec::new(),
+            roster: Roster::default(),
+        };
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            save.parse_directive(&words)
+                .map_err(|message| CampaignError::Parse { line: index + 1, message })?;
+        }
+        Ok(save)
+    }
+
+    fn parse_directive(&mut self, words: &[&str]) -> Result<(), String> {
+        match words[0] {
+            "campaign" => self.campaign = words.get(1).ok_or_else(|| "expected a campaign".to_owned())?.to_string(),
+            "souls" => self.souls = parse_number(words.get(1))?,
+            "available" => self.available = words[1..].iter().map(|word| word.to_string()).collect(),
+            "completed" => self.completed = words[1..].iter().map(|word| word.to_string()).collect(),
+            "veteran" => self.roster.veterans.push(parse_veteran(words)?),
+            directive => return Err(format!("unknown directive '{}'", directive)),
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<CampaignSave, CampaignError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        CampaignSave::parse(&text[..])
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CampaignError> {
+        let mut file = File::create(path)?;
+        file.write_all(self.to_string().as_bytes())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for CampaignSave {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "campaign {}", self.campaign)?;
+        writeln!(f, "souls {}", self.souls)?;
+        writeln!(f, "available {}", self.available.join(" "))?;
+        writeln!(f, "completed {}", self.completed.join(" "))?;
+        write!(f, "{}", self.roster)
+    }
+}
+
+fn parse_number<T: ::std::str::FromStr>(word: Option<&&str>) -> Result<T, String> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| "expected a number".to_owned())
+}
+
+pub fn find(name: &str) -> Option<PathBuf> {
+    let direct = PathBuf::from(name);
+    if direct.is_file() {
+        return Some(direct);
+    }
+    find_resource(&Path::new(CAMPAIGN_DIR).join(name).with_extension(CAMPAIGN_EXTENSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roster::Veteran;
+
+    const NIGHT: &str = "name The Night\n\
+                         node crossing skirmish 0 1 10\n\
+                         node road duel 1 1 5\n\
+                         node keep royal_guard 2 1 25\n\
+                         win crossing keep\n\
+                         lose crossing road\n\
+                         win road keep";
+
+    fn save(veterans: Vec<Veteran>) -> CampaignSave {
+        CampaignSave {
+            campaign: "night".to_owned(),
+            souls: 0,
+            available: vec!["crossing".to_owned()],
+            completed: Vec::new(),
+            roster: Roster { veterans },
+        }
+    }
+
+    #[test]
+    fn defeat_takes_the_loss_branch() {
+        let campaign = Campaign::parse("night", NIGHT).unwrap();
+        let mut save = save(vec![Veteran::new("Mortis", UnitClass::Warrior)]);
+        save.record_battle(&campaign, "crossing", false);
+        assert_eq!(save.available, vec!["road".to_owned()]);
+        assert!(save.completed.is_empty());
+        assert_eq!(save.souls, 0);
+        assert_eq!(save.status(), CampaignStatus::InProgress);
+    }
+
+    #[test]
+    fn defeat_without_a_loss_branch_means_fighting_again() {
+        let campaign = Campaign::parse("night", NIGHT).unwrap();
+        let mut save = save(vec![Veteran::new("Mortis", UnitClass::Warrior)]);
+        save.available = vec!["road".to_owned()];
+        save.record_battle(&campaign, "road", false);
+        assert_eq!(save.available, vec!["road".to_owned()]);
+    }
+
+    #[test]
+    fn losing_the_whole_army_ends_the_campaign() {
+        let campaign = Campaign::parse("night", NIGHT).unwrap();
+        let mut save = save(Vec::new());
+        save.souls = RECRUIT_COST - 1;
+        let messages = save.record_battle(&campaign, "crossing", false);
+        assert_eq!(save.status(), CampaignStatus::Lost);
+        assert_eq!(messages, vec!["The Night is lost".to_owned()]);
+        save.souls = RECRUIT_COST;
+        assert_eq!(save.status(), CampaignStatus::InProgress);
+    }
+
+    #[test]
+    fn saves_read_back_the_same() {
+        let campaign = Campaign::parse("night", NIGHT).unwrap();
+        let mut save = save(vec![Veteran::new("Mortis", UnitClass::Warrior)]);
+        save.record_battle(&campaign, "crossing", true);
+        assert_eq!(save.available, vec!["keep".to_owned()]);
+        assert_eq!(save.souls, 10);
+        assert_eq!(CampaignSave::parse(&save.to_string()).unwrap(), save);
+    }
+}
@@ -0,0 +1,243 @@
+use battle::{Battle, Command as BattleCommand};
+use game_object::*;
+
+pub type CommandResult = Result<String, String>;
+pub type CommandFn = fn(&mut Battle, &[&str]) -> CommandResult;
+
+pub struct Command {
+    name: &'static str,
+    usage: &'static str,
+    run: CommandFn,
+    /// False for commands that only look at the battle.
+    changes_battle: bool,
+}
+
+impl Command {
+    fn words(&self) -> Vec<&'static str> {
+        self.name.split_whitespace().collect()
+    }
+}
+
+#[derive(Default, new)]
+pub struct CommandRegistry {
+    #[new(default)]
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    pub fn with_builtins() -> Self {
+        let mut registry = CommandRegistry::new();
+        register_builtins(&mut registry);
+        registry
+    }
+
+    /// Command names may span several words, e.g. "load scenario".
+    pub fn register(&mut self, name: &'static str, usage: &'static str, run: CommandFn) {
+        self.add(Command { name, usage, run, changes_battle: true });
+    }
+
+    /// Registers a command that leaves the battle as it was.
+    pub fn register_query(&mut self, name: &'static str, usage: &'static str, run: CommandFn) {
+        self.add(Command { name, usage, run, changes_battle: false });
+    }
+
+    fn add(&mut self, command: Command) {
+        self.commands.retain(|existing| existing.name != command.name);
+        self.commands.push(command);
+    }
+
+    fn find(&self, words: &[&str]) -> Option<&Command> {
+        self.commands
+            .iter()
+            .filter(|command| {
+                let name = command.words();
+                words.len() >= name.len() && name[..] == words[..name.len()]
+            })
+            .max_by_key(|command| command.words().len())
+    }
+
+    /// Whether running the line could have changed the battle. Help and
+    /// unknown commands never do.
+    pub fn changes_battle(&self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        self.find(&words).map(|command| command.changes_battle) == Some(true)
+    }
+
+    pub fn execute(&self, battle: &mut Battle, line: &str) -> CommandResult {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            return Ok(String::new());
+        }
+        if words[0] == "help" {
+            return Ok(self.help());
+        }
+        match self.find(&words) {
+            Some(command) => (command.run)(battle, &words[command.words().len()..])
+                .map_err(|e| format!("{} (usage: {})", e, command.usage)),
+            None => Err(format!("unknown command '{}'", words[0])),
+        }
+    }
+
+    pub fn complete(&self, line: &str) -> Vec<&'static str> {
+        let line = line.trim();
+        let mut candidates: Vec<&'static str> = self.commands
+            .iter()
+            .map(|command| command.name)
+            .chain(Some("help"))
+            .filter(|name| name.starts_with(line))
+            .collect();
+        candidates.sort();
+        candidates
+    }
+
+    fn help(&self) -> String {
+        let mut usages: Vec<&str> = self.commands.iter().map(|command| command.usage).collect();
+        usages.sort();
+        usages.join("\n")
+    }
+}
+
+pub fn parse_unit_id(arg: Option<&&str>) -> Result<UnitId, String> {
+    arg.and_then(|arg| arg.parse().ok())
+        .map(UnitId::new)
+        .ok_or_else(|| "expected a unit id".to_owned())
+}
+
+pub fn parse_tile(x: Option<&&str>, y: Option<&&str>) -> Result<Tile, String> {
+    match (x.and_then(|x| x.parse().ok()), y.and_then(|y| y.parse().ok())) {
+        (Some(x), Some(y)) => Ok(Tile::new(x, y)),
+        _ => Err("expected tile coordinates".to_owned()),
+    }
+}
+
+pub fn parse_side(arg: Option<&&str>) -> Result<Side, String> {
+    arg.and_then(|arg| Side::from_name(arg))
+        .ok_or_else(|| "expected 'player' or 'enemy'".to_owned())
+}
+
+fn register_builtins(registry: &mut CommandRegistry) {
+    registry.register("spawn", "spawn <class> <side> <x> <y>", spawn);
+    registry.register("kill", "kill <id>", kill);
+    registry.register("heal", "heal <id>", heal);
+    registry.register("teleport", "teleport <id> <x> <y>", teleport);
+    registry.register("move", "move <id> <x> <y>", move_unit);
+    registry.register("attack", "attack <id> <target>", attack);
+    registry.register("setturn", "setturn <side>", set_turn);
+    registry.register("endturn", "endturn", end_turn);
+    registry.register("effect", "effect <id> <kind> <turns> [magnitude]", add_effect);
+    registry.register("seed", "seed <n>", seed);
+    registry.register_query("units", "units", list_units);
+}
+
+fn spawn(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let class = args.get(0)
+        .and_then(|arg| UnitClass::from_name(arg))
+        .ok_or_else(|| "unknown unit class".to_owned())?;
+    let side = parse_side(args.get(1))?;
+    let tile = parse_tile(args.get(2), args.get(3))?;
+    if battle.units().unit_at(tile).is_some() {
+        return Err(format!("tile {},{} is occupied", tile.x(), tile.y()));
+    }
+    let id = battle.units_mut().make_unit(class.spawn(tile, side));
+    Ok(format!("spawned {} {} as {}", side.name(), class.name(), id))
+}
+
+fn kill(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let id = parse_unit_id(args.get(0))?;
+    battle.slay(id)
+        .map(|_| format!("killed {}", id))
+        .map_err(|e| e.to_string())
+}
+
+fn heal(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let id = parse_unit_id(args.get(0))?;
+    battle.restore(id)
+        .map(|_| format!("healed {}", id))
+        .map_err(|e| e.to_string())
+}
+
+fn teleport(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let id = parse_unit_id(args.get(0))?;
+    let tile = parse_tile(args.get(1), args.get(2))?;
+    if battle.units().unit_at(tile).is_some() {
+        return Err(format!("tile {},{} is occupied", tile.x(), tile.y()));
+    }
+    let unit = battle.units_mut()
+        .get_unit_mut(id)
+        .ok_or_else(|| format!("no unit {}", id))?;
+    unit.set_tile(tile);
+    Ok(format!("teleported {} to {},{}", id, tile.x(), tile.y()))
+}
+
+fn issue(battle: &mut Battle, command: BattleCommand) -> CommandResult {
+    let events = battle.apply(command).map_err(|e| e.to_string())?;
+    let lines: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+    Ok(lines.join("\n"))
+}
+
+fn move_unit(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let unit = parse_unit_id(args.get(0))?;
+    let to = parse_tile(args.get(1), args.get(2))?;
+    issue(battle, BattleCommand::Move { unit, to })
+}
+
+fn attack(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let unit = parse_unit_id(args.get(0))?;
+    let target = parse_unit_id(args.get(1))?;
+    issue(battle, BattleCommand::Attack { unit, target })
+}
+
+fn set_turn(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let side = parse_side(args.get(0))?;
+    battle.set_turn(side);
+    Ok(format!("{} to move", side.name()))
+}
+
+fn end_turn(battle: &mut Battle, _args: &[&str]) -> CommandResult {
+    let lines: Vec<String> = battle.end_turn().iter().map(|event| event.to_string()).collect();
+    Ok(lines.join("\n"))
+}
+
+fn add_effect(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let unit = parse_unit_id(args.get(0))?;
+    let kind = args.get(1)
+        .and_then(|arg| EffectKind::from_name(arg))
+        .ok_or_else(|| "expected an effect kind".to_owned())?;
+    let turns = args.get(2)
+        .and_then(|arg| arg.parse().ok())
+        .ok_or_else(|| "expected a number of turns".to_owned())?;
+    let magnitude = match args.get(3) {
+        Some(arg) => arg.parse().map_err(|_| "expected a magnitude".to_owned())?,
+        None => 1,
+    };
+    let event = battle
+        .add_effect(unit, StatusEffect::new(kind, turns, magnitude))
+        .map_err(|e| e.to_string())?;
+    Ok(event.to_string())
+}
+
+fn seed(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let seed = args.get(0)
+        .and_then(|arg| arg.parse().ok())
+        .ok_or_else(|| "expected a number".to_owned())?;
+    battle.reseed(seed);
+    Ok(format!("seed set to {}", seed))
+}
+
+fn list_units(battle: &mut Battle, _args: &[&str]) -> CommandResult {
+    let mut lines: Vec<String> = battle.units()
+        .iter_with_ids()
+        .map(|(id, unit)| {
+            format!(
+                "{} {} {} at {},{}",
+                id,
+                unit.get_side().name(),
+                unit.get_class().name(),
+                unit.get_tile_x(),
+                unit.get_tile_y()
+            )
+        })
+        .collect();
+    lines.sort();
+    Ok(lines.join("\n"))
+}
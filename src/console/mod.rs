@@ -0,0 +1,7 @@
+pub mod commands;
+#[cfg(feature = "graphics")]
+mod window;
+
+pub use self::commands::{CommandFn, CommandRegistry, CommandResult};
+#[cfg(feature = "graphics")]
+pub use self::window::Console;
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use battle::Battle;
+use ggez::event::Keycode;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::{Context, GameResult};
+use main_state::PixelFont;
+use super::CommandRegistry;
+
+const PROMPT: &str = "# ";
+const MAX_OUTPUT_LINES: usize = 12;
+const TOGGLE_CHARACTER: &str = "`";
+const LINE_HEIGHT: f32 = 7.0;
+const PADDING: f32 = 2.0;
+
+pub struct Console {
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    history_cursor: Option<usize>,
+    output: VecDeque<String>,
+    registry: CommandRegistry,
+    font: PixelFont,
+}
+
+impl Console {
+    pub fn new(registry: CommandRegistry, font: PixelFont) -> Self {
+        Self {
+            open: false,
+            input: PROMPT.to_owned(),
+            history: Vec::new(),
+            history_cursor: None,
+            output: VecDeque::new(),
+            registry,
+            font,
+        }
+    }
+
+    pub fn registry_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.registry
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.output.len() == MAX_OUTPUT_LINES {
+                self.output.pop_front();
+            }
+            self.output.push_back(line.to_owned());
+        }
+    }
+
+    fn input_text(&self) -> &str {
+        &self.input[PROMPT.len()..]
+    }
+
+    fn set_input(&mut self, text: &str) {
+        self.input = format!("{}{}", PROMPT, text);
+    }
+
+    pub fn text_input(&mut self, text: &str) {
+        if text == TOGGLE_CHARACTER {
+            return;
+        }
+        let new_input = format!("{}{}", self.input_text(), text);
+        self.set_input(&new_input[..]);
+    }
+
+    /// Returns whether a command has just changed the battle.
+    pub fn key_down(&mut self, battle: &mut Battle, keycode: Keycode) -> bool {
+        match keycode {
+            Keycode::Return => return self.submit(battle),
+            Keycode::Backspace => {
+                let mut new_input = self.input_text().to_owned();
+                new_input.pop();
+                self.set_input(&new_input[..]);
+            }
+            Keycode::Tab => self.complete(),
+            Keycode::Up => self.browse_history(true),
+            Keycode::Down => self.browse_history(false),
+            Keycode::Escape => self.open = false,
+            _ => (),
+        }
+        false
+    }
+
+    /// Runs the line, returning whether it changed the battle. Commands
+    /// that fail leave the battle alone.
+    pub fn execute(&mut self, battle: &mut Battle, line: &str) -> bool {
+        match self.registry.execute(battle, line) {
+            Ok(message) => {
+                self.print(&message[..]);
+                self.registry.changes_battle(line)
+            }
+            Err(message) => {
+                self.print(&format!("error: {}", message)[..]);
+                false
+            }
+        }
+    }
+
+    fn submit(&mut self, battle: &mut Battle) -> bool {
+        let line = self.input_text().trim().to_owned();
+        self.set_input("");
+        self.history_cursor = None;
+        if line.is_empty() {
+            return false;
+        }
+        self.print(&format!("{}{}", PROMPT, line)[..]);
+        self.history.push(line.clone());
+        self.execute(battle, &line[..])
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let newest = self.history.len() - 1;
+        self.history_cursor = match (self.history_cursor, older) {
+            (None, true) => Some(newest),
+            (None, false) => None,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i < newest => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        let text = match self.history_cursor {
+            Some(i) => self.history[i].clone(),
+            None => String::new(),
+        };
+        self.set_input(&text[..]);
+    }
+
+    fn complete(&mut self) {
+        let candidates = self.registry.complete(self.input_text());
+        match candidates.len() {
+            0 => (),
+            1 => {
+                let completed = format!("{} ", candidates[0]);
+                self.set_input(&completed[..]);
+            }
+            _ => {
+                let prefix = common_prefix(&candidates);
+                self.set_input(&prefix[..]);
+                self.print(&candidates.join("  ")[..]);
+            }
+        }
+    }
+
+    pub fn render(&self, ctx: &mut Context, width: u32) -> GameResult<()> {
+        if !self.open {
+            return Ok(());
+        }
+        let height = LINE_HEIGHT * (self.output.len() + 1) as f32 + 2.0 * PADDING;
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.75))?;
+        graphics::rectangle(ctx, DrawMode::Fill, Rect::new(0.0, 0.0, width as f32, height))?;
+        graphics::set_color(ctx, graphics::WHITE)?;
+        let mut cursor = Point2::new(PADDING, PADDING);
+        for line in self.output.iter().chain(Some(&self.input)) {
+            self.font.draw(ctx, line, cursor, 1.0)?;
+            cursor.y += LINE_HEIGHT;
+        }
+        Ok(())
+    }
+}
+
+fn common_prefix(candidates: &[&str]) -> String {
+    let first = candidates[0];
+    let mut length = first.len();
+    for candidate in &candidates[1..] {
+        length = first
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|&(a, b)| a == b)
+            .count()
+            .min(length);
+    }
+    first[..length].to_owned()
+}
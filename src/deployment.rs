@@ -0,0 +1,177 @@
+use std::error::Error;
+use std::fmt;
+use game_object::*;
+use roster::Roster;
+use scenario::Scenario;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum DeploymentError {
+    NoSuchVeteran(usize),
+    OutsideZone(Tile),
+    OverBudget { cost: u32, budget: u32 },
+    NobodyDeployed,
+    /// The scenario is lost without a unit of this class.
+    MissingProtected(UnitClass),
+}
+
+impl fmt::Display for DeploymentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeploymentError::NoSuchVeteran(index) => write!(f, "no veteran {} in the roster", index + 1),
+            DeploymentError::OutsideZone(tile) => {
+                write!(f, "{},{} is outside the deployment zone", tile.x(), tile.y())
+            }
+            DeploymentError::OverBudget { cost, budget } => {
+                write!(f, "the army costs {} points but the budget is {}", cost, budget)
+            }
+            DeploymentError::NobodyDeployed => write!(f, "deploy at least one unit"),
+            DeploymentError::MissingProtected(class) => write!(f, "a {} has to be deployed", class.name()),
+        }
+    }
+}
+
+impl Error for DeploymentError {
+    fn description(&self) -> &str {
+        "invalid deployment"
+    }
+}
+
+/// Which veterans go where before a battle starts.
+#[derive(Clone, Debug)]
+pub struct DeploymentPlan {
+    zone: Vec<Tile>,
+    budget: Option<u32>,
+    protected: Vec<UnitClass>,
+    /// Roster indices and the tiles they start on.
+    placements: Vec<(usize, Tile)>,
+}
+
+impl DeploymentPlan {
+    /// An empty plan for the scenario's zone, leaving out tiles that are
+    /// walls or already held by the enemy.
+    pub fn for_scenario(scenario: &Scenario) -> Self {
+        let battle = scenario.to_battle(0);
+        let zone = scenario
+            .deployment_zone()
+            .into_iter()
+            .filter(|tile| battle.in_bounds(*tile) && battle.map().move_cost(*tile).is_some())
+            .filter(|tile| {
+                battle.units().unit_at(*tile)
+                    .and_then(|id| battle.units().get_unit(id))
+                    .map(|unit| unit.get_side() == Side::Player)
+                    .unwrap_or(true)
+            })
+            .collect();
+        Self {
+            zone,
+            budget: scenario.budget,
+            protected: scenario.protected_classes(),
+            placements: Vec::new(),
+        }
+    }
+
+    /// Fills the zone with veterans in roster order, skipping any that
+    /// would go over the budget.
+    pub fn automatic(scenario: &Scenario, roster: &Roster) -> Self {
+        let mut plan = DeploymentPlan::for_scenario(scenario);
+        for index in 0..roster.veterans.len() {
+            let tile = match plan.zone.iter().find(|tile| plan.veteran_at(**tile).is_none()) {
+                Some(tile) => *tile,
+                None => break,
+            };
+            let _ = plan.place(roster, index, tile);
+        }
+        plan
+    }
+
+    pub fn zone(&self) -> &[Tile] {
+        &self.zone
+    }
+
+    pub fn budget(&self) -> Option<u32> {
+        self.budget
+    }
+
+    pub fn placements(&self) -> &[(usize, Tile)] {
+        &self.placements
+    }
+
+    pub fn tile_of(&self, index: usize) -> Option<Tile> {
+        self.placements
+            .iter()
+            .find(|&&(placed, _)| placed == index)
+            .map(|&(_, tile)| tile)
+    }
+
+    pub fn veteran_at(&self, tile: Tile) -> Option<usize> {
+        self.placements
+            .iter()
+            .find(|&&(_, placed)| placed == tile)
+            .map(|&(index, _)| index)
+    }
+
+    pub fn cost(&self, roster: &Roster) -> u32 {
+        self.placements
+            .iter()
+            .filter_map(|&(index, _)| roster.veterans.get(index))
+            .map(|veteran| veteran.cost())
+            .sum()
+    }
+
+    /// Puts the veteran on `tile`, moving it if it was placed elsewhere and
+    /// sending home whoever stood there.
+    pub fn place(&mut self, roster: &Roster, index: usize, tile: Tile) -> Result<(), DeploymentError> {
+        if index >= roster.veterans.len() {
+            return Err(DeploymentError::NoSuchVeteran(index));
+        }
+        if !self.zone.contains(&tile) {
+            return Err(DeploymentError::OutsideZone(tile));
+        }
+        let mut placements = self.placements.clone();
+        placements.retain(|&(placed, at)| placed != index && at != tile);
+        placements.push((index, tile));
+        let plan = DeploymentPlan {
+            placements,
+            ..self.clone()
+        };
+        plan.check_budget(roster)?;
+        *self = plan;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.placements.retain(|&(placed, _)| placed != index);
+    }
+
+    fn check_budget(&self, roster: &Roster) -> Result<(), DeploymentError> {
+        let cost = self.cost(roster);
+        match self.budget {
+            Some(budget) if cost > budget => Err(DeploymentError::OverBudget { cost, budget }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the battle can start with this plan.
+    pub fn validate(&self, roster: &Roster) -> Result<(), DeploymentError> {
+        if self.placements.is_empty() {
+            return Err(DeploymentError::NobodyDeployed);
+        }
+        for &(index, tile) in &self.placements {
+            if index >= roster.veterans.len() {
+                return Err(DeploymentError::NoSuchVeteran(index));
+            }
+            if !self.zone.contains(&tile) {
+                return Err(DeploymentError::OutsideZone(tile));
+            }
+        }
+        for class in &self.protected {
+            let deployed = self.placements
+                .iter()
+                .any(|&(index, _)| roster.veterans[index].class == *class);
+            if !deployed {
+                return Err(DeploymentError::MissingProtected(*class));
+            }
+        }
+        self.check_budget(roster)
+    }
+}
@@ -0,0 +1,70 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use battle::BattleEvent;
+use game_object::*;
+
+/// Something that happened in the game that other parts of it may want to
+/// react to without polling for it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    /// Something the rules made happen on `side`'s turn.
+    Battle { side: Side, turn: u32, event: BattleEvent },
+    MenuSelected,
+    /// Something went wrong that the game carries on through, for the
+    /// player to be told about.
+    Warning(String),
+}
+
+impl GameEvent {
+    /// A short name for the kind of event, as used in data files.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            GameEvent::Battle { ref event, .. } => event.name(),
+            GameEvent::MenuSelected => "ui-select",
+            GameEvent::Warning(_) => "warning",
+        }
+    }
+}
+
+pub trait Subscriber {
+    fn notify(&mut self, event: &GameEvent);
+}
+
+/// Hands every event published since the last dispatch to the subscribers,
+/// in the order they were published.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Rc<RefCell<dyn Subscriber>>>,
+    queue: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        EventBus::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: Rc<RefCell<dyn Subscriber>>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Queues the event until the next dispatch.
+    pub fn publish(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    pub fn publish_all<I: IntoIterator<Item = GameEvent>>(&mut self, events: I) {
+        self.queue.extend(events);
+    }
+
+    /// Returns the events for anything that can't subscribe itself, like
+    /// scenes.
+    pub fn dispatch(&mut self) -> Vec<GameEvent> {
+        let events: Vec<GameEvent> = self.queue.drain(..).collect();
+        for event in &events {
+            for subscriber in &self.subscribers {
+                subscriber.borrow_mut().notify(event);
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use game_object::effects::{EffectKind, StatusEffect};
+use game_object::*;
+
+/// What an ability can be aimed at.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Targeting {
+    /// The caster's own tile; the range is ignored.
+    Caster,
+    Ally,
+    Enemy,
+    /// Any tile that isn't a wall.
+    Tile,
+    /// A tile with a corpse and nobody standing on it.
+    Corpse,
+}
+
+impl Targeting {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Targeting::Caster => "self",
+            Targeting::Ally => "ally",
+            Targeting::Enemy => "enemy",
+            Targeting::Tile => "tile",
+            Targeting::Corpse => "corpse",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum AbilityEffect {
+    Damage(u16, DamageType),
+    Heal(u16),
+    Status(StatusEffect),
+    /// Brings a corpse back on the caster's side with this much health.
+    Raise(u16),
+}
+
+impl AbilityEffect {
+    /// Harmful effects land on the caster's enemies within the area and
+    /// helpful ones on its allies.
+    pub fn is_harmful(&self) -> bool {
+        match *self {
+            AbilityEffect::Damage(..) => true,
+            AbilityEffect::Status(effect) => match effect.kind {
+                EffectKind::Poison | EffectKind::Stun | EffectKind::Weakness => true,
+                _ => false,
+            },
+            AbilityEffect::Heal(_) | AbilityEffect::Raise(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Ability {
+    pub name: &'static str,
+    /// Turns of the caster's side before it can be used again.
+    pub cooldown: u16,
+    pub targeting: Targeting,
+    /// Offsets from the caster it can be aimed at, like an attack pattern.
+    pub range: HashSet<Tile>,
+    /// Offsets from the target tile it affects.
+    pub area: HashSet<Tile>,
+    pub effects: Vec<AbilityEffect>,
+}
+
+impl Ability {
+    pub fn is_harmful(&self) -> bool {
+        self.effects.iter().any(|effect| effect.is_harmful())
+    }
+
+    /// Whether it heals or raises units, which makes its owner a healer.
+    pub fn restores(&self) -> bool {
+        self.effects.iter().any(|effect| match *effect {
+            AbilityEffect::Heal(_) | AbilityEffect::Raise(_) => true,
+            _ => false,
+        })
+    }
+
+    pub fn shield_bash() -> Ability {
+        Ability {
+            name: "shield bash",
+            cooldown: 3,
+            targeting: Targeting::Enemy,
+            range: Unit::melee_attack_pattern(),
+            area: single_tile(),
+            effects: vec![
+                AbilityEffect::Damage(2, DamageType::Physical),
+                AbilityEffect::Status(StatusEffect::new(EffectKind::Stun, 1, 1)),
+            ],
+        }
+    }
+
+    pub fn poison_arrow() -> Ability {
+        Ability {
+            name: "poison arrow",
+            cooldown: 2,
+            targeting: Targeting::Enemy,
+            range: Unit::shoot_attack_pattern(4),
+            area: single_tile(),
+            effects: vec![
+                AbilityEffect::Damage(1, DamageType::Physical),
+                AbilityEffect::Status(StatusEffect::new(EffectKind::Poison, 2, 2)),
+            ],
+        }
+    }
+
+    pub fn rally() -> Ability {
+        Ability {
+            name: "rally",
+            cooldown: 4,
+            targeting: Targeting::Caster,
+            range: single_tile(),
+            area: radius_pattern(2),
+            effects: vec![
+                AbilityEffect::Status(StatusEffect::new(EffectKind::Haste, 2, 1)),
+                AbilityEffect::Status(StatusEffect::new(EffectKind::Ward, 2, 1)),
+            ],
+        }
+    }
+
+    pub fn heal() -> Ability {
+        Ability {
+            name: "heal",
+            cooldown: 1,
+            targeting: Targeting::Ally,
+            range: radius_pattern(2),
+            area: single_tile(),
+            effects: vec![AbilityEffect::Heal(4)],
+        }
+    }
+
+    pub fn resurrect() -> Ability {
+        Ability {
+            name: "resurrect",
+            cooldown: 5,
+            targeting: Targeting::Corpse,
+            range: Unit::melee_attack_pattern(),
+            area: single_tile(),
+            effects: vec![AbilityEffect::Raise(3)],
+        }
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// An ability a unit knows, with the turns left until it's ready again.
+#[derive(Clone, Debug)]
+pub struct AbilitySlot {
+    pub ability: Arc<Ability>,
+    pub cooldown_left: u16,
+}
+
+impl AbilitySlot {
+    pub fn new(ability: Ability) -> Self {
+        Self {
+            ability: Arc::new(ability),
+            cooldown_left: 0,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.cooldown_left == 0
+    }
+}
+
+fn single_tile() -> HashSet<Tile> {
+    let mut pattern = HashSet::new();
+    pattern.insert(Tile::new(0, 0));
+    pattern
+}
+
+/// Every offset within `radius` steps, the centre included.
+pub fn radius_pattern(radius: i16) -> HashSet<Tile> {
+    let mut pattern = HashSet::new();
+    for x in -radius..radius + 1 {
+        for y in -radius..radius + 1 {
+            if x.abs() + y.abs() <= radius {
+                pattern.insert(Tile::new(x, y));
+            }
+        }
+    }
+    pattern
+}
@@ -0,0 +1,166 @@
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum EffectKind {
+    /// Loses `magnitude` health at the start of each of its turns.
+    Poison,
+    /// Regains `magnitude` health at the start of each of its turns.
+    Regeneration,
+    /// Can't move or attack.
+    Stun,
+    /// Each hit taken is `magnitude` weaker. Unlike a shield, which soaks up
+    /// damage until it's used up, a ward lasts for its duration.
+    Ward,
+    /// Moves `magnitude` tiles further.
+    Haste,
+    /// Hits `magnitude` weaker.
+    Weakness,
+}
+
+/// What happens when an effect lands on a unit that already has it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Stacking {
+    /// Keep the longer duration and the stronger magnitude.
+    Refresh,
+    /// Add the magnitudes together and keep the longer duration.
+    Intensify,
+}
+
+impl EffectKind {
+    pub fn all() -> Vec<EffectKind> {
+        vec![
+            EffectKind::Poison,
+            EffectKind::Regeneration,
+            EffectKind::Stun,
+            EffectKind::Ward,
+            EffectKind::Haste,
+            EffectKind::Weakness,
+        ]
+    }
+
+    pub fn from_name(name: &str) -> Option<EffectKind> {
+        EffectKind::all().into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            EffectKind::Poison => "poison",
+            EffectKind::Regeneration => "regeneration",
+            EffectKind::Stun => "stun",
+            EffectKind::Ward => "ward",
+            EffectKind::Haste => "haste",
+            EffectKind::Weakness => "weakness",
+        }
+    }
+
+    pub fn stacking(&self) -> Stacking {
+        match *self {
+            EffectKind::Poison => Stacking::Intensify,
+            _ => Stacking::Refresh,
+        }
+    }
+}
+
+impl fmt::Display for EffectKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Constructor)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    /// Turns of the affected unit's side left before it wears off.
+    pub turns: u16,
+    pub magnitude: u16,
+}
+
+/// Health change an effect causes at the start of its unit's turn.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tick {
+    Damage(u16),
+    Heal(u16),
+}
+
+#[derive(Clone, Debug, Default, Hash)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.effects.iter()
+    }
+
+    pub fn get(&self, kind: EffectKind) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.kind == kind)
+    }
+
+    pub fn has(&self, kind: EffectKind) -> bool {
+        self.get(kind).is_some()
+    }
+
+    fn magnitude(&self, kind: EffectKind) -> u16 {
+        self.get(kind).map(|effect| effect.magnitude).unwrap_or(0)
+    }
+
+    pub fn add(&mut self, effect: StatusEffect) {
+        match self.effects.iter_mut().find(|existing| existing.kind == effect.kind) {
+            Some(existing) => {
+                existing.turns = existing.turns.max(effect.turns);
+                existing.magnitude = match effect.kind.stacking() {
+                    Stacking::Refresh => existing.magnitude.max(effect.magnitude),
+                    Stacking::Intensify => existing.magnitude.saturating_add(effect.magnitude),
+                };
+            }
+            None => self.effects.push(effect),
+        }
+    }
+
+    pub fn remove(&mut self, kind: EffectKind) {
+        self.effects.retain(|effect| effect.kind != kind);
+    }
+
+    pub fn prevents_acting(&self) -> bool {
+        self.has(EffectKind::Stun)
+    }
+
+    pub fn movement_modifier(&self) -> i32 {
+        self.magnitude(EffectKind::Haste) as i32
+    }
+
+    pub fn damage_modifier(&self) -> i32 {
+        -(self.magnitude(EffectKind::Weakness) as i32)
+    }
+
+    /// Damage left of a hit after effects like wards have softened it.
+    pub fn incoming_damage(&self, damage: u16) -> u16 {
+        damage.saturating_sub(self.magnitude(EffectKind::Ward))
+    }
+
+    /// The start of turn hook: health changes from effects over time.
+    pub fn start_of_turn(&self) -> Vec<(EffectKind, Tick)> {
+        self.effects
+            .iter()
+            .filter_map(|effect| match effect.kind {
+                EffectKind::Poison => Some((effect.kind, Tick::Damage(effect.magnitude))),
+                EffectKind::Regeneration => Some((effect.kind, Tick::Heal(effect.magnitude))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The end of turn hook: counts every effect down a turn, returning the
+    /// ones that wore off.
+    pub fn end_of_turn(&mut self) -> Vec<EffectKind> {
+        let mut expired = Vec::new();
+        for effect in &mut self.effects {
+            effect.turns = effect.turns.saturating_sub(1);
+            if effect.turns == 0 {
+                expired.push(effect.kind);
+            }
+        }
+        self.effects.retain(|effect| effect.turns > 0);
+        expired
+    }
+}
@@ -0,0 +1,397 @@
+use std::fmt;
+use game_object::Side;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DamageType {
+    /// Blunted by armor.
+    Physical,
+    Magic,
+    /// Burns the undead.
+    Holy,
+}
+
+impl DamageType {
+    pub fn all() -> Vec<DamageType> {
+        vec![DamageType::Physical, DamageType::Magic, DamageType::Holy]
+    }
+
+    pub fn from_name(name: &str) -> Option<DamageType> {
+        DamageType::all().into_iter().find(|damage_type| damage_type.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            DamageType::Physical => "physical",
+            DamageType::Magic => "magic",
+            DamageType::Holy => "holy",
+        }
+    }
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Percentage of each damage type shrugged off. Negative values are
+/// weaknesses: -100 doubles the damage.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Resistances {
+    pub physical: i16,
+    pub magic: i16,
+    pub holy: i16,
+}
+
+impl Resistances {
+    pub fn undead() -> Self {
+        Self {
+            holy: -100,
+            ..Resistances::default()
+        }
+    }
+
+    /// The player's army is undead.
+    pub fn of(side: Side) -> Self {
+        match side {
+            Side::Player => Resistances::undead(),
+            Side::Enemy => Resistances::default(),
+        }
+    }
+
+    pub fn get(&self, damage_type: DamageType) -> i16 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Magic => self.magic,
+            DamageType::Holy => self.holy,
+        }
+    }
+
+    /// Damage left after the resistance, which is at most 100%.
+    pub fn apply(&self, damage: u16, damage_type: DamageType) -> u16 {
+        // Worked out in i64, as a strong weakness times a big hit overflows i32.
+        let percent = 100 - (self.get(damage_type) as i64).min(100);
+        clamp_u16(damage as i64 * percent / 100)
+    }
+}
+
+/// What became of a single hit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DamageReport {
+    pub damage_type: DamageType,
+    /// The hit as it landed, before resistances and armor.
+    pub raw: u16,
+    /// Taken off by resistances and armor, zero when a weakness made it worse.
+    pub resisted: u16,
+    /// Soaked up by the temporary shield.
+    pub absorbed: u16,
+    /// Health actually lost.
+    pub dealt: u16,
+    /// Damage beyond what was needed to kill.
+    pub overkill: u16,
+    pub remaining: i16,
+    pub killed: bool,
+}
+
+impl fmt::Display for DamageReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.dealt, self.damage_type)?;
+        if self.resisted > 0 {
+            write!(f, ", {} resisted", self.resisted)?;
+        }
+        if self.absorbed > 0 {
+            write!(f, ", {} absorbed", self.absorbed)?;
+        }
+        write!(f, " ({} left)", self.remaining)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Health {
+    max: u16,
+    current: i16,
+    /// Temporary hit points lost before health.
+    shield: u16,
+    /// Flat reduction of physical hits.
+    armor: u16,
+    resistances: Resistances,
+}
+
+impl Health {
+    pub fn new(health: u16) -> Self {
+        let max = health.min(i16::max_value() as u16);
+        Self {
+            max,
+            current: max as i16,
+            shield: 0,
+            armor: 0,
+            resistances: Resistances::default(),
+        }
+    }
+
+    pub fn with_armor(mut self, armor: u16) -> Self {
+        self.armor = armor;
+        self
+    }
+
+    pub fn with_resistances(mut self, resistances: Resistances) -> Self {
+        self.resistances = resistances;
+        self
+    }
+
+    /// Takes a hit through resistances, armor and the shield.
+    pub fn take_hit(&mut self, damage: u16, damage_type: DamageType) -> DamageReport {
+        let mut mitigated = self.resistances.apply(damage, damage_type);
+        if damage_type == DamageType::Physical {
+            mitigated = mitigated.saturating_sub(self.armor);
+        }
+        let mut report = self.take_damage(mitigated);
+        report.damage_type = damage_type;
+        report.raw = damage;
+        report.resisted = damage.saturating_sub(mitigated);
+        report
+    }
+
+    /// Loses health straight away, only the shield gets in the way.
+    pub fn take_damage(&mut self, damage: u16) -> DamageReport {
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+        let left = damage - absorbed;
+        let before = self.current.max(0) as u16;
+        let dealt = left.min(before);
+        self.current = clamp_i16(self.current as i32 - left as i32).max(0);
+        DamageReport {
+            damage_type: DamageType::Physical,
+            raw: damage,
+            resisted: 0,
+            absorbed,
+            dealt,
+            overkill: left - dealt,
+            remaining: self.current,
+            killed: self.is_dead() && dealt > 0,
+        }
+    }
+
+    /// Heals up to `max`, returning how much was actually restored.
+    pub fn heal(&mut self, amount: u16) -> u16 {
+        let before = self.current;
+        self.current = clamp_i16(self.current as i32 + amount as i32).min(self.max as i16);
+        (self.current - before).max(0) as u16
+    }
+
+    /// Heals, turning whatever goes past `max` into shield. Returns the
+    /// health restored and the shield gained.
+    pub fn overheal(&mut self, amount: u16) -> (u16, u16) {
+        let healed = self.heal(amount);
+        let shielded = self.add_shield(amount - healed);
+        (healed, shielded)
+    }
+
+    /// Adds temporary hit points, up to half of `max`. Returns how many
+    /// were gained.
+    pub fn add_shield(&mut self, amount: u16) -> u16 {
+        let before = self.shield;
+        self.shield = self.shield.saturating_add(amount).min(self.max_shield());
+        self.shield.saturating_sub(before)
+    }
+
+    pub fn max_shield(&self) -> u16 {
+        self.max / 2
+    }
+
+    /// Raises `max` and heals by the same amount.
+    pub fn grow(&mut self, amount: u16) {
+        self.max = self.max.saturating_add(amount).min(i16::max_value() as u16);
+        self.heal(amount);
+    }
+
+    pub fn restore(&mut self) {
+        self.current = self.max as i16;
+        self.shield = 0;
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0
+    }
+
+    pub fn get_current(&self) -> i16 {
+        self.current
+    }
+
+    pub fn get_max(&self) -> u16 {
+        self.max
+    }
+
+    pub fn get_shield(&self) -> u16 {
+        self.shield
+    }
+
+    pub fn get_armor(&self) -> u16 {
+        self.armor
+    }
+
+    pub fn get_resistances(&self) -> Resistances {
+        self.resistances
+    }
+}
+
+fn clamp_u16(value: i64) -> u16 {
+    value.max(0).min(u16::max_value() as i64) as u16
+}
+
+fn clamp_i16(value: i32) -> i16 {
+    value.max(i16::min_value() as i32).min(i16::max_value() as i32) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use battle::rng_from_seed;
+    use rand::{Rng, XorShiftRng};
+
+    const CASES: usize = 10_000;
+
+    /// Anywhere in the type's range, with the edges turning up often.
+    fn any_u16(rng: &mut XorShiftRng) -> u16 {
+        match rng.gen_range(0, 8) {
+            0 => 0,
+            1 => u16::max_value(),
+            2 => rng.gen_range(0, 20),
+            _ => rng.gen(),
+        }
+    }
+
+    fn any_i16(rng: &mut XorShiftRng) -> i16 {
+        match rng.gen_range(0, 8) {
+            0 => i16::min_value(),
+            1 => i16::max_value(),
+            2 => rng.gen_range(-150, 150),
+            _ => rng.gen(),
+        }
+    }
+
+    fn any_damage_type(rng: &mut XorShiftRng) -> DamageType {
+        *rng.choose(&DamageType::all()).unwrap()
+    }
+
+    /// A unit's health part way through a fight.
+    fn any_health(rng: &mut XorShiftRng) -> Health {
+        let resistances = Resistances {
+            physical: any_i16(rng),
+            magic: any_i16(rng),
+            holy: any_i16(rng),
+        };
+        let mut health = Health::new(any_u16(rng)).with_armor(any_u16(rng)).with_resistances(resistances);
+        health.add_shield(any_u16(rng));
+        health.take_damage(any_u16(rng));
+        health
+    }
+
+    fn check_invariants(health: &Health) {
+        assert!(health.get_current() >= 0, "{:?}", health);
+        assert!(health.get_current() <= health.get_max() as i16, "{:?}", health);
+        assert!(health.get_shield() <= health.max_shield(), "{:?}", health);
+    }
+
+    #[test]
+    fn resistances_never_overflow() {
+        let mut rng = rng_from_seed(1);
+        for _ in 0..CASES {
+            let resistance = any_i16(&mut rng);
+            let resistances = Resistances { physical: resistance, magic: resistance, holy: resistance };
+            let damage = any_u16(&mut rng);
+            let taken = resistances.apply(damage, any_damage_type(&mut rng));
+            if resistance >= 100 {
+                assert_eq!(taken, 0);
+            } else if resistance >= 0 {
+                assert!(taken <= damage);
+            } else {
+                assert!(taken >= damage);
+            }
+        }
+        let weakest = Resistances { holy: i16::min_value(), ..Resistances::default() };
+        assert_eq!(weakest.apply(u16::max_value(), DamageType::Holy), u16::max_value());
+    }
+
+    #[test]
+    fn hits_account_for_every_point_of_damage() {
+        let mut rng = rng_from_seed(2);
+        for _ in 0..CASES {
+            let mut health = any_health(&mut rng);
+            let (before, shield) = (health.get_current(), health.get_shield());
+            let damage_type = any_damage_type(&mut rng);
+            let report = health.take_hit(any_u16(&mut rng), damage_type);
+            let taken = report.dealt as u32 + report.overkill as u32;
+            let landed = taken + report.absorbed as u32;
+            assert_eq!(report.resisted as u32, (report.raw as u32).saturating_sub(landed), "{:?}", report);
+            // Only a weakness can make a hit land harder than it was dealt.
+            if health.get_resistances().get(damage_type) >= 0 {
+                assert_eq!(taken, (report.raw - report.resisted - report.absorbed) as u32, "{:?}", report);
+            }
+            assert_eq!(report.dealt as i16, before - health.get_current());
+            assert_eq!(report.absorbed, shield - health.get_shield());
+            assert_eq!(report.remaining, health.get_current());
+            assert_eq!(report.killed, before > 0 && health.is_dead());
+            check_invariants(&health);
+        }
+    }
+
+    #[test]
+    fn health_stays_within_bounds() {
+        let mut rng = rng_from_seed(3);
+        for _ in 0..CASES / 10 {
+            let mut health = any_health(&mut rng);
+            for _ in 0..10 {
+                match rng.gen_range(0, 5) {
+                    0 => {
+                        health.take_hit(any_u16(&mut rng), any_damage_type(&mut rng));
+                    }
+                    1 => {
+                        health.heal(any_u16(&mut rng));
+                    }
+                    2 => {
+                        health.overheal(any_u16(&mut rng));
+                    }
+                    3 => {
+                        health.add_shield(any_u16(&mut rng));
+                    }
+                    _ => health.grow(any_u16(&mut rng)),
+                }
+                check_invariants(&health);
+            }
+        }
+    }
+
+    #[test]
+    fn overheal_is_capped() {
+        let mut rng = rng_from_seed(4);
+        for _ in 0..CASES {
+            let mut health = any_health(&mut rng);
+            let (before, shield) = (health.get_current(), health.get_shield());
+            let amount = any_u16(&mut rng);
+            let (healed, shielded) = health.overheal(amount);
+            assert!(healed as u32 + shielded as u32 <= amount as u32);
+            assert_eq!(healed as i16, health.get_current() - before);
+            assert_eq!(shielded, health.get_shield() - shield);
+            if healed < amount {
+                assert_eq!(health.get_current(), health.get_max() as i16);
+            }
+            check_invariants(&health);
+        }
+    }
+
+    #[test]
+    fn healing_undoes_a_hit() {
+        let mut rng = rng_from_seed(5);
+        for _ in 0..CASES {
+            let mut health = any_health(&mut rng);
+            let before = health.get_current();
+            let report = health.take_hit(any_u16(&mut rng), any_damage_type(&mut rng));
+            if report.killed || before == 0 {
+                continue;
+            }
+            assert_eq!(health.heal(report.dealt), report.dealt);
+            assert_eq!(health.get_current(), before);
+        }
+    }
+}
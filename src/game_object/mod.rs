@@ -1,5 +1,15 @@
 use assets::SpriteName;
 use std::collections::{hash_map::Values, HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+pub mod abilities;
+pub mod effects;
+pub mod health;
+
+pub use self::abilities::{Ability, AbilityEffect, AbilitySlot, Targeting};
+pub use self::effects::{EffectKind, StatusEffect, StatusEffects};
+pub use self::health::{DamageReport, DamageType, Health, Resistances};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Add, Sub, AddAssign, SubAssign, From, Into,
          Constructor, Mul, MulAssign)]
@@ -16,6 +26,19 @@ impl Tile {
     pub fn y(&self) -> i32 {
         self.y as i32
     }
+
+    pub fn neighbours(&self) -> Vec<Tile> {
+        vec![
+            *self + Tile::new(1, 0),
+            *self + Tile::new(-1, 0),
+            *self + Tile::new(0, 1),
+            *self + Tile::new(0, -1),
+        ]
+    }
+
+    pub fn distance(&self, other: Tile) -> u32 {
+        ((self.x() - other.x()).abs() + (self.y() - other.y()).abs()) as u32
+    }
 }
 
 pub trait HasSprite {
@@ -34,46 +57,128 @@ pub trait HasTile {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Side {
     Player,
     Enemy,
 }
 
-pub struct Health {
-    max: u16,
-    current: i16,
+impl Side {
+    pub fn from_name(name: &str) -> Option<Side> {
+        match name {
+            "player" => Some(Side::Player),
+            "enemy" => Some(Side::Enemy),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Side::Player => "player",
+            Side::Enemy => "enemy",
+        }
+    }
+
+    pub fn opponent(&self) -> Side {
+        match *self {
+            Side::Player => Side::Enemy,
+            Side::Enemy => Side::Player,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum UnitClass {
+    Warrior,
+    Archer,
+    King,
+    Monk,
+    Champion,
+    Wizard,
+    Necromancer,
 }
 
-impl Health {
-    pub fn new(health: u16) -> Self {
-        Self {
-            max: health,
-            current: health as i16,
+impl UnitClass {
+    pub fn all() -> Vec<UnitClass> {
+        vec![
+            UnitClass::Warrior,
+            UnitClass::Archer,
+            UnitClass::King,
+            UnitClass::Monk,
+            UnitClass::Champion,
+            UnitClass::Wizard,
+            UnitClass::Necromancer,
+        ]
+    }
+
+    pub fn from_name(name: &str) -> Option<UnitClass> {
+        UnitClass::all().into_iter().find(|class| class.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            UnitClass::Warrior => "warrior",
+            UnitClass::Archer => "archer",
+            UnitClass::King => "king",
+            UnitClass::Monk => "monk",
+            UnitClass::Champion => "champion",
+            UnitClass::Wizard => "wizard",
+            UnitClass::Necromancer => "necromancer",
         }
     }
-    pub fn take_damage(&mut self, damage: u16) {
-        self.current -= damage as i16;
+
+    pub fn spawn(&self, tile: Tile, side: Side) -> Unit {
+        match *self {
+            UnitClass::Warrior => Unit::warrior(tile, side),
+            UnitClass::Archer => Unit::archer(tile, side),
+            UnitClass::King => Unit::king(tile, side),
+            UnitClass::Monk => Unit::monk(tile, side),
+            UnitClass::Champion => Unit::champion(tile, side),
+            UnitClass::Wizard => Unit::wizard(tile, side),
+            UnitClass::Necromancer => Unit::necromancer(tile, side),
+        }
     }
 
-    pub fn is_dead(&self) -> bool {
-        self.current <= 0
+    /// Points it takes out of a deployment budget.
+    pub fn cost(&self) -> u32 {
+        match *self {
+            UnitClass::Warrior | UnitClass::Archer => 3,
+            UnitClass::Monk => 4,
+            UnitClass::King | UnitClass::Champion | UnitClass::Wizard => 5,
+            UnitClass::Necromancer => 6,
+        }
+    }
+
+    /// The advanced class a veteran of this class can be promoted into.
+    pub fn promotion(&self) -> Option<UnitClass> {
+        match *self {
+            UnitClass::Warrior => Some(UnitClass::Champion),
+            UnitClass::Archer => Some(UnitClass::Wizard),
+            UnitClass::Monk => Some(UnitClass::Necromancer),
+            _ => None,
+        }
     }
 }
 
-#[derive(HasSprite, HasTile)]
+#[derive(Clone, HasSprite, HasTile)]
 pub struct Unit {
+    class: UnitClass,
     side: Side,
     movement: u16,
     damage: u16,
+    damage_type: DamageType,
+    vision: u16,
     attack_pattern: HashSet<Tile>,
     sprite: SpriteName,
     health: Health,
+    effects: StatusEffects,
+    abilities: Vec<AbilitySlot>,
+    level: u16,
     tile: Tile,
 }
 
 impl Unit {
-    fn melee_attack_pattern() -> HashSet<Tile> {
+    pub fn melee_attack_pattern() -> HashSet<Tile> {
         let mut attack_pattern = HashSet::new();
         attack_pattern.insert(Tile::new(1, 0));
         attack_pattern.insert(Tile::new(-1, 0));
@@ -82,7 +187,7 @@ impl Unit {
         attack_pattern
     }
 
-    fn shoot_attack_pattern(range: u16) -> HashSet<Tile> {
+    pub fn shoot_attack_pattern(range: u16) -> HashSet<Tile> {
         let mut attack_pattern = HashSet::new();
         for x in 0..range as i16 {
             for y in 0..range as i16 {
@@ -101,12 +206,18 @@ impl Unit {
             Side::Player => SpriteName::UndeadWarrior,
         };
         Self {
+            class: UnitClass::Warrior,
             side,
             movement: 3,
             damage: 4,
+            damage_type: DamageType::Physical,
+            vision: 4,
             attack_pattern: Self::melee_attack_pattern(),
             sprite,
-            health: Health::new(10),
+            health: Health::new(10).with_armor(1).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![AbilitySlot::new(Ability::shield_bash())],
+            level: 1,
             tile,
         }
     }
@@ -117,12 +228,134 @@ impl Unit {
             Side::Player => SpriteName::UndeadArcher,
         };
         Self {
+            class: UnitClass::Archer,
             side,
             movement: 3,
             damage: 2,
+            damage_type: DamageType::Physical,
+            vision: 5,
             attack_pattern: Self::shoot_attack_pattern(3),
             sprite,
-            health: Health::new(5),
+            health: Health::new(5).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![AbilitySlot::new(Ability::poison_arrow())],
+            level: 1,
+            tile,
+        }
+    }
+
+    pub fn king(tile: Tile, side: Side) -> Self {
+        let sprite = match side {
+            Side::Enemy => SpriteName::King,
+            Side::Player => SpriteName::UndeadKing,
+        };
+        Self {
+            class: UnitClass::King,
+            side,
+            movement: 2,
+            damage: 3,
+            damage_type: DamageType::Physical,
+            vision: 3,
+            attack_pattern: Self::melee_attack_pattern(),
+            sprite,
+            health: Health::new(12).with_armor(1).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![AbilitySlot::new(Ability::rally())],
+            level: 1,
+            tile,
+        }
+    }
+
+    pub fn monk(tile: Tile, side: Side) -> Self {
+        let sprite = match side {
+            Side::Enemy => SpriteName::Monk,
+            Side::Player => SpriteName::UndeadMonk,
+        };
+        Self {
+            class: UnitClass::Monk,
+            side,
+            movement: 3,
+            damage: 1,
+            damage_type: DamageType::Holy,
+            vision: 4,
+            attack_pattern: Self::melee_attack_pattern(),
+            sprite,
+            health: Health::new(6).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![
+                AbilitySlot::new(Ability::heal()),
+                AbilitySlot::new(Ability::resurrect()),
+            ],
+            level: 1,
+            tile,
+        }
+    }
+
+    pub fn champion(tile: Tile, side: Side) -> Self {
+        let sprite = match side {
+            Side::Enemy => SpriteName::Champion,
+            Side::Player => SpriteName::UndeadChampion,
+        };
+        Self {
+            class: UnitClass::Champion,
+            side,
+            movement: 3,
+            damage: 5,
+            damage_type: DamageType::Physical,
+            vision: 4,
+            attack_pattern: Self::melee_attack_pattern(),
+            sprite,
+            health: Health::new(14).with_armor(2).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![AbilitySlot::new(Ability::shield_bash())],
+            level: 1,
+            tile,
+        }
+    }
+
+    pub fn wizard(tile: Tile, side: Side) -> Self {
+        let sprite = match side {
+            Side::Enemy => SpriteName::Wizard,
+            Side::Player => SpriteName::UndeadWizard,
+        };
+        Self {
+            class: UnitClass::Wizard,
+            side,
+            movement: 3,
+            damage: 3,
+            damage_type: DamageType::Magic,
+            vision: 5,
+            attack_pattern: Self::shoot_attack_pattern(4),
+            sprite,
+            health: Health::new(6).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![AbilitySlot::new(Ability::poison_arrow())],
+            level: 1,
+            tile,
+        }
+    }
+
+    pub fn necromancer(tile: Tile, side: Side) -> Self {
+        let sprite = match side {
+            Side::Enemy => SpriteName::Necromancer,
+            Side::Player => SpriteName::UndeadNecromancer,
+        };
+        Self {
+            class: UnitClass::Necromancer,
+            side,
+            movement: 3,
+            damage: 2,
+            damage_type: DamageType::Magic,
+            vision: 4,
+            attack_pattern: Self::melee_attack_pattern(),
+            sprite,
+            health: Health::new(8).with_resistances(Resistances::of(side)),
+            effects: StatusEffects::default(),
+            abilities: vec![
+                AbilitySlot::new(Ability::heal()),
+                AbilitySlot::new(Ability::resurrect()),
+            ],
+            level: 1,
             tile,
         }
     }
@@ -130,18 +363,125 @@ impl Unit {
     pub fn get_side(&self) -> Side {
         self.side
     }
+
+    pub fn get_class(&self) -> UnitClass {
+        self.class
+    }
+
+    /// Movement after status effects.
+    pub fn get_movement(&self) -> u16 {
+        (self.movement as i32 + self.effects.movement_modifier()).max(0) as u16
+    }
+
+    pub fn get_base_movement(&self) -> u16 {
+        self.movement
+    }
+
+    /// Damage after status effects.
+    pub fn get_damage(&self) -> u16 {
+        (self.damage as i32 + self.effects.damage_modifier()).max(0) as u16
+    }
+
+    pub fn get_base_damage(&self) -> u16 {
+        self.damage
+    }
+
+    pub fn get_level(&self) -> u16 {
+        self.level
+    }
+
+    /// Grows the unit's stats to those of a veteran of `level`: a point of
+    /// health every level and a point of damage every other level.
+    pub fn with_level(mut self, level: u16) -> Self {
+        let gained = level.saturating_sub(self.level);
+        self.health.grow(gained);
+        self.damage += (level / 2).saturating_sub(self.level / 2);
+        self.level = self.level.max(level);
+        self
+    }
+
+    pub fn get_damage_type(&self) -> DamageType {
+        self.damage_type
+    }
+
+    /// Takes a hit after status effects like shields have softened it.
+    pub fn receive_hit(&mut self, damage: u16, damage_type: DamageType) -> DamageReport {
+        let damage = self.effects.incoming_damage(damage);
+        self.health.take_hit(damage, damage_type)
+    }
+
+    pub fn get_effects(&self) -> &StatusEffects {
+        &self.effects
+    }
+
+    pub fn get_effects_mut(&mut self) -> &mut StatusEffects {
+        &mut self.effects
+    }
+
+    /// How many steps away the unit can see, walls permitting.
+    pub fn get_vision(&self) -> u16 {
+        self.vision
+    }
+
+    pub fn get_attack_pattern(&self) -> &HashSet<Tile> {
+        &self.attack_pattern
+    }
+
+    pub fn get_health(&self) -> &Health {
+        &self.health
+    }
+
+    pub fn get_health_mut(&mut self) -> &mut Health {
+        &mut self.health
+    }
+
+    pub fn get_abilities(&self) -> &[AbilitySlot] {
+        &self.abilities
+    }
+
+    /// Healers are singled out by the focus-fire AI personality: any unit
+    /// with an ability that heals or raises.
+    pub fn is_healer(&self) -> bool {
+        self.abilities.iter().any(|slot| slot.ability.restores())
+    }
+
+    pub fn get_ability(&self, index: usize) -> Option<&AbilitySlot> {
+        self.abilities.get(index)
+    }
+
+    pub fn start_cooldown(&mut self, index: usize) {
+        if let Some(slot) = self.abilities.get_mut(index) {
+            slot.cooldown_left = slot.ability.cooldown;
+        }
+    }
+
+    pub fn tick_cooldowns(&mut self) {
+        for slot in &mut self.abilities {
+            slot.cooldown_left = slot.cooldown_left.saturating_sub(1);
+        }
+    }
+
+    pub fn set_tile(&mut self, tile: Tile) {
+        self.tile = tile;
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Default, Constructor)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Constructor)]
 pub struct UnitId(u32);
 
+impl fmt::Display for UnitId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl UnitId {
     pub fn next(&self) -> Self {
         UnitId(self.0 + 1)
     }
 }
 
-#[derive(Default, new)]
+#[derive(Clone, Default, new)]
 pub struct Units {
     #[new(default)]
     units: HashMap<UnitId, Unit>,
@@ -154,6 +494,25 @@ impl Units {
         self.units.values()
     }
 
+    pub fn iter_with_ids(&self) -> impl Iterator<Item = (&UnitId, &Unit)> {
+        self.units.iter()
+    }
+
+    pub fn get_unit(&self, unit_id: UnitId) -> Option<&Unit> {
+        self.units.get(&unit_id)
+    }
+
+    pub fn unit_at(&self, tile: Tile) -> Option<UnitId> {
+        self.units
+            .iter()
+            .find(|&(_, unit)| unit.get_tile() == tile)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn remove_unit(&mut self, unit_id: UnitId) -> Option<Unit> {
+        self.units.remove(&unit_id)
+    }
+
     pub fn make_unit(&mut self, unit: Unit) -> UnitId {
         let next_id = self.last_id.next();
         self.units.insert(next_id, unit);
@@ -164,4 +523,34 @@ impl Units {
     pub fn get_unit_mut(&mut self, unit_id: UnitId) -> Option<&mut Unit> {
         self.units.get_mut(&unit_id)   
     }
+
+    /// Feeds everything that matters to the rules into `state`, in id order
+    /// so equal positions always hash the same.
+    pub fn hash_state<H: Hasher>(&self, state: &mut H) {
+        let mut ids: Vec<&UnitId> = self.units.keys().collect();
+        ids.sort();
+        for id in ids {
+            let unit = &self.units[id];
+            id.hash(state);
+            unit.class.hash(state);
+            unit.side.hash(state);
+            unit.tile.hash(state);
+            unit.health.get_current().hash(state);
+            unit.health.get_shield().hash(state);
+            unit.effects.hash(state);
+            for slot in &unit.abilities {
+                slot.cooldown_left.hash(state);
+            }
+        }
+    }
+
+    pub fn ids_of(&self, side: Side) -> Vec<UnitId> {
+        let mut ids: Vec<UnitId> = self.units
+            .iter()
+            .filter(|&(_, unit)| unit.get_side() == side)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
 }
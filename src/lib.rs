@@ -0,0 +1,35 @@
+#[macro_use]
+extern crate derive_new;
+#[macro_use]
+extern crate derive_more;
+#[macro_use]
+extern crate derive;
+#[cfg(feature = "graphics")]
+extern crate ggez;
+extern crate rand;
+
+pub mod ai;
+pub mod assets;
+#[cfg(feature = "graphics")]
+pub mod audio;
+pub mod battle;
+pub mod campaign;
+pub mod console;
+pub mod deployment;
+pub mod event_bus;
+#[cfg(feature = "graphics")]
+pub mod main_state;
+pub mod map;
+pub mod roster;
+pub mod game_object;
+pub mod scenario;
+#[cfg(feature = "graphics")]
+pub mod scene;
+#[cfg(feature = "graphics")]
+pub mod settings;
+pub mod simulation;
+pub mod statistics;
+pub mod utils;
+pub mod viewport;
+#[macro_use]
+mod macros;
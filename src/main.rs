@@ -1,42 +1,53 @@
-#[macro_use]
-extern crate derive_new;
-#[macro_use]
-extern crate derive_more;
-#[macro_use]
-extern crate derive;
 extern crate ggez;
-extern crate rand;
+extern crate oga_summer_2018;
 
+use std::process;
 use ggez::conf;
 use ggez::event::*;
-use ggez::ContextBuilder;
+use ggez::filesystem::Filesystem;
+use ggez::{ContextBuilder, GameError};
+use oga_summer_2018::main_state::{PixelFont, TitleScene, GAME_TITLE};
+use oga_summer_2018::scene::SceneStack;
+use oga_summer_2018::settings::Settings;
+use oga_summer_2018::viewport::CANVAS_SIZE;
 
-mod assets;
-mod main_state;
-mod game_object;
-mod utils;
-#[macro_use]
-mod macros;
+const GAME_ID: &str = "gift";
+const AUTHOR: &str = "ggez";
 
 fn main() {
-    let (width, height) = (800, 450);
-    let cb = ContextBuilder::new("gift", "ggez")
-        .window_setup(conf::WindowSetup::default().title("WINDOW_TITLE"))
-        .window_mode(conf::WindowMode::default().dimensions(width, height));
+    let settings = match Filesystem::new(GAME_ID, AUTHOR) {
+        Ok(filesystem) => Settings::load_or_default(filesystem.get_user_config_dir()).unwrap_or_else(|e| {
+            println!("Ignoring the saved settings: {}", e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    };
+    let cb = ContextBuilder::new(GAME_ID, AUTHOR)
+        .window_setup(conf::WindowSetup::default().title(GAME_TITLE).resizable(true))
+        .window_mode(settings.window_mode());
 
-    let ctx = &mut cb.build().unwrap();
-    match main_state::MainState::new(ctx, width, height) {
+    let ctx = &mut match cb.build() {
+        Ok(ctx) => ctx,
         Err(e) => {
-            println!("Could not load game!");
-            println!("Error: {}", e);
-        }
-        Ok(ref mut game) => {
-            let result = run(ctx, game);
-            if let Err(e) = result {
-                println!("Error encountered running game: {}", e);
-            } else {
-                println!("Game exited cleanly.");
+            // ggez can't open a window without a sound device, so there's
+            // nothing to fall back to here. The headless tools still run.
+            println!("Could not start the game: {}", e);
+            if let GameError::AudioError(_) = e {
+                println!("No sound device was found. The simulate and tournament tools run without one.");
             }
+            process::exit(1);
         }
+    };
+    let result = PixelFont::load(ctx)
+        .map_err(GameError::from)
+        .and_then(|font| {
+            let title = Box::new(TitleScene::new(CANVAS_SIZE.0, CANVAS_SIZE.1, settings.clone(), font));
+            SceneStack::new(ctx, &settings, title)
+        })
+        .and_then(|ref mut game| run(ctx, game));
+    if let Err(e) = result {
+        println!("Error encountered running game: {}", e);
+    } else {
+        println!("Game exited cleanly.");
     }
 }
@@ -0,0 +1,89 @@
+use battle::Battle;
+use game_object::*;
+use ggez::event::Keycode;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::Context;
+use main_state::pixel_font::PixelFont;
+
+const PADDING: f32 = 2.0;
+const LINE_HEIGHT: f32 = 7.0;
+
+/// The selected unit's plain attack or one of its abilities.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MenuChoice {
+    Attack,
+    Ability(usize),
+}
+
+impl MenuChoice {
+    /// Number keys pick entries in the order the menu lists them.
+    pub fn from_key(keycode: Keycode) -> Option<MenuChoice> {
+        let index = match keycode {
+            Keycode::Num1 => 0,
+            Keycode::Num2 => 1,
+            Keycode::Num3 => 2,
+            Keycode::Num4 => 3,
+            Keycode::Num5 => 4,
+            _ => return None,
+        };
+        if index == 0 {
+            Some(MenuChoice::Attack)
+        } else {
+            Some(MenuChoice::Ability(index - 1))
+        }
+    }
+}
+
+/// Lists what the selected unit can do next to it on the map.
+pub struct ActionMenu {
+    font: PixelFont,
+}
+
+impl ActionMenu {
+    pub fn new(font: PixelFont) -> Self {
+        Self { font }
+    }
+
+    fn lines(battle: &Battle, unit_id: UnitId, choice: MenuChoice) -> Vec<String> {
+        let unit = match battle.units().get_unit(unit_id) {
+            Some(unit) => unit,
+            None => return Vec::new(),
+        };
+        let marker = |entry: MenuChoice| if entry == choice { "*" } else { " " };
+        let mut lines = vec![format!("{}1 attack", marker(MenuChoice::Attack))];
+        for (index, slot) in unit.get_abilities().iter().enumerate() {
+            let status = if slot.is_ready() {
+                format!("({})", slot.ability.targeting.name())
+            } else {
+                format!("({} turns)", slot.cooldown_left)
+            };
+            lines.push(format!(
+                "{}{} {} {}",
+                marker(MenuChoice::Ability(index)),
+                index + 2,
+                slot.ability.name,
+                status
+            ));
+        }
+        lines
+    }
+
+    pub fn render(&mut self, ctx: &mut Context, battle: &Battle, unit_id: UnitId, choice: MenuChoice, position: Point2) {
+        let lines = ActionMenu::lines(battle, unit_id, choice);
+        if lines.is_empty() {
+            return;
+        }
+        let width = lines.iter().map(|line| self.font.width(line, 1.0)).fold(0.0, f32::max);
+        let height = lines.len() as f32 * LINE_HEIGHT;
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.75)).unwrap();
+        let background = Rect::new(position.x, position.y, width + 2.0 * PADDING, height + 2.0 * PADDING);
+        graphics::rectangle(ctx, DrawMode::Fill, background).unwrap();
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        let mut cursor = Point2::new(position.x + PADDING, position.y + PADDING);
+        for line in &lines {
+            self.font.draw(ctx, line, cursor, 1.0).unwrap();
+            cursor.y += LINE_HEIGHT;
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use ai::profile::scenario_ai;
+use ai::{Ai, GreedyAi, Timing};
+use battle::{Battle, BattleEvent};
+use campaign::{Campaign, CampaignError, CampaignSave, CampaignStatus, RECRUIT_COST};
+use deployment::DeploymentPlan;
+use game_object::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::Context;
+use main_state::pixel_font::PixelFont;
+use roster::{Deployment, Progress};
+use scenario::Scenario;
+use std::path::Path;
+
+const ORIGIN: (f32, f32) = (8.0, 32.0);
+const NODE_SPACING: (f32, f32) = (62.0, 34.0);
+const NODE_SIZE: (f32, f32) = (54.0, 17.0);
+const ROSTER_X: f32 = 194.0;
+const LINE_HEIGHT: f32 = 7.0;
+
+/// A campaign being played, and the battle of it being fought if any.
+pub struct CampaignRun {
+    pub campaign: Campaign,
+    pub save: CampaignSave,
+    pub battle: Option<CampaignBattle>,
+}
+
+pub struct CampaignBattle {
+    pub node: String,
+    pub deployment: Deployment,
+    /// Everything that happened, for handing out experience afterwards.
+    pub events: Vec<BattleEvent>,
+    pub enemy: Box<dyn Ai>,
+}
+
+impl CampaignRun {
+    /// Continues the save in `path`, or starts the default campaign if
+    /// there is none.
+    pub fn load_or_start(path: &Path, campaign: &str) -> Result<CampaignRun, CampaignError> {
+        let save = if path.is_file() {
+            CampaignSave::load(path)?
+        } else {
+            CampaignSave::new(&Campaign::load(campaign)?)?
+        };
+        let campaign = Campaign::load(&save.campaign[..])?;
+        Ok(CampaignRun { campaign, save, battle: None })
+    }
+
+    pub fn start_new(campaign: &str) -> Result<CampaignRun, CampaignError> {
+        let campaign = Campaign::load(campaign)?;
+        let save = CampaignSave::new(&campaign)?;
+        Ok(CampaignRun { campaign, save, battle: None })
+    }
+
+    /// The scenario at `node` and a first go at deploying the roster into it.
+    pub fn prepare(&self, node: &str) -> Result<(Scenario, DeploymentPlan), String> {
+        if self.save.status() != CampaignStatus::InProgress {
+            return Err(format!("{} is over, N starts it again", self.campaign.name));
+        }
+        if self.save.roster.veterans.is_empty() {
+            return Err("every veteran has fallen, R recruits another".to_owned());
+        }
+        if !self.save.is_available(node) {
+            return Err(format!("{} can't be fought now", node));
+        }
+        let scenario_name = self.campaign.node(node).map(|node| node.scenario.clone()).unwrap();
+        let scenario = Scenario::load(&scenario_name[..]).map_err(|e| e.to_string())?;
+        let plan = DeploymentPlan::automatic(&scenario, &self.save.roster);
+        Ok((scenario, plan))
+    }
+
+    /// Sets up the battle at `node` with the roster deployed as planned.
+    pub fn start_battle(&mut self, node: &str, scenario: &Scenario, plan: &DeploymentPlan, seed: u64) -> Result<Battle, String> {
+        plan.validate(&self.save.roster).map_err(|e| e.to_string())?;
+        let (battle, deployment) = self.save.roster.deploy(scenario, plan, seed);
+        let enemy = match scenario_ai(scenario, Side::Enemy, &battle, seed.wrapping_add(1), Timing::Interactive)? {
+            Some(ai) => ai,
+            None => Box::new(GreedyAi::new(seed.wrapping_add(1))),
+        };
+        self.battle = Some(CampaignBattle {
+            node: node.to_owned(),
+            deployment,
+            events: Vec::new(),
+            enemy,
+        });
+        Ok(battle)
+    }
+
+    /// Hands out experience and moves the campaign on once `battle` is over.
+    pub fn finish_battle(&mut self, battle: &Battle) -> Vec<String> {
+        let fought = match self.battle.take() {
+            Some(fought) => fought,
+            None => return Vec::new(),
+        };
+        let won = battle.winner() == Some(Side::Player);
+        let mut messages = vec![format!("{} {}", fought.node, if won { "won" } else { "lost" })];
+        let progress = self.save
+            .roster
+            .record_battle(&fought.deployment, battle, &fought.events);
+        messages.extend(progress.iter().map(Progress::to_string));
+        messages.extend(self.save.record_battle(&self.campaign, &fought.node[..], won));
+        messages
+    }
+
+    pub fn recruit(&mut self, class: UnitClass) -> Result<String, String> {
+        self.save.recruit(class).map(|progress| progress.to_string())
+    }
+}
+
+/// The node map between battles, where the next one is chosen.
+pub struct CampaignMap {
+    font: PixelFont,
+}
+
+impl CampaignMap {
+    pub fn new(font: PixelFont) -> Self {
+        Self { font }
+    }
+
+    fn node_rect(position: (i32, i32)) -> Rect {
+        Rect::new(
+            ORIGIN.0 + position.0 as f32 * NODE_SPACING.0,
+            ORIGIN.1 + position.1 as f32 * NODE_SPACING.1,
+            NODE_SIZE.0,
+            NODE_SIZE.1,
+        )
+    }
+
+    fn centre(rect: Rect) -> Point2 {
+        Point2::new(rect.x + rect.w / 2.0, rect.y + rect.h / 2.0)
+    }
+
+    /// The node drawn under the screen position, if any.
+    pub fn node_at(campaign: &Campaign, x: f32, y: f32) -> Option<String> {
+        campaign
+            .nodes
+            .iter()
+            .find(|node| {
+                let rect = CampaignMap::node_rect(node.position);
+                x >= rect.x && x <= rect.x + rect.w && y >= rect.y && y <= rect.y + rect.h
+            })
+            .map(|node| node.id.clone())
+    }
+
+    fn text(&self, ctx: &mut Context, text: &str, position: Point2) {
+        self.font.draw(ctx, text, position, 1.0).unwrap();
+    }
+
+    pub fn render(&self, ctx: &mut Context, run: &CampaignRun) {
+        let campaign = &run.campaign;
+        let save = &run.save;
+        let status = match save.status() {
+            CampaignStatus::InProgress => "choose the next battle",
+            CampaignStatus::Won => "victory",
+            CampaignStatus::Lost => "defeat",
+        };
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        self.text(ctx, &format!("{} - {}", campaign.name, status), Point2::new(ORIGIN.0, 4.0));
+        self.text(ctx, &format!("souls: {}", save.souls), Point2::new(ORIGIN.0, 4.0 + LINE_HEIGHT));
+        self.text(
+            ctx,
+            &format!("R recruits a warrior ({} souls), N starts over, Esc returns", RECRUIT_COST),
+            Point2::new(ORIGIN.0, 4.0 + 2.0 * LINE_HEIGHT),
+        );
+
+        for (from, to, won) in campaign.links() {
+            let (from, to) = match (campaign.node(from), campaign.node(to)) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+            let color = if won {
+                Color::new(0.8, 0.8, 0.8, 1.0)
+            } else {
+                Color::new(0.7, 0.2, 0.2, 1.0)
+            };
+            graphics::set_color(ctx, color).unwrap();
+            let points = [
+                CampaignMap::centre(CampaignMap::node_rect(from.position)),
+                CampaignMap::centre(CampaignMap::node_rect(to.position)),
+            ];
+            graphics::line(ctx, &points, 1.0).unwrap();
+        }
+
+        for node in &campaign.nodes {
+            let color = if save.is_available(&node.id[..]) {
+                Color::new(0.8, 0.6, 0.1, 1.0)
+            } else if save.is_completed(&node.id[..]) {
+                Color::new(0.3, 0.3, 0.3, 1.0)
+            } else {
+                Color::new(0.12, 0.12, 0.2, 1.0)
+            };
+            let rect = CampaignMap::node_rect(node.position);
+            graphics::set_color(ctx, color).unwrap();
+            graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+            graphics::set_color(ctx, graphics::WHITE).unwrap();
+            self.text(ctx, &node.id, Point2::new(rect.x + 2.0, rect.y + 2.0));
+            self.text(ctx, &node.scenario, Point2::new(rect.x + 2.0, rect.y + 2.0 + LINE_HEIGHT));
+        }
+
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        let mut cursor = Point2::new(ROSTER_X, ORIGIN.1);
+        self.text(ctx, "roster", cursor);
+        for veteran in &save.roster.veterans {
+            cursor.y += LINE_HEIGHT;
+            let line = format!(
+                "{} {} lv{} {}/{}xp",
+                veteran.name,
+                veteran.class.name(),
+                veteran.level,
+                veteran.xp,
+                veteran.xp_to_next_level()
+            );
+            self.text(ctx, &line, cursor);
+        }
+    }
+}
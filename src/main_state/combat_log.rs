@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use battle::{Battle, BattleEvent};
+use game_object::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::Context;
+use main_state::overlay::side_color;
+use main_state::pixel_font::PixelFont;
+use settings::Palette;
+
+const WIDTH: f32 = 176.0;
+const LINE_HEIGHT: f32 = 7.0;
+const PADDING: f32 = 3.0;
+/// Entries scrolled by one notch of the mouse wheel or a page key.
+pub const SCROLL_STEP: isize = 3;
+
+/// One line of the log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub turn: u32,
+    /// Whose turn it happened on.
+    pub side: Side,
+    pub text: String,
+    /// The units it's about, to highlight on the map.
+    pub units: Vec<UnitId>,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "turn {} ({}): {}", self.turn, self.side.name(), self.text)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogButton {
+    Filter,
+    Export,
+}
+
+/// What is remembered about a unit so it can still be named after it dies.
+#[derive(Clone, Copy, Debug)]
+struct KnownUnit {
+    class: UnitClass,
+    side: Side,
+    max_health: u16,
+}
+
+impl KnownUnit {
+    fn of(unit: &Unit) -> Self {
+        KnownUnit {
+            class: unit.get_class(),
+            side: unit.get_side(),
+            max_health: unit.get_health().get_max(),
+        }
+    }
+}
+
+/// "Undead Archer" for the player's units and just "Archer" for the enemy's.
+pub fn unit_name(class: UnitClass, side: Side) -> String {
+    let name = class.name();
+    let capitalised = name[..1].to_uppercase() + &name[1..];
+    match side {
+        Side::Player => format!("Undead {}", capitalised),
+        Side::Enemy => capitalised,
+    }
+}
+
+/// The battle as it happened, one readable line per event, shown in a panel
+/// down the right of the screen.
+pub struct CombatLog {
+    entries: Vec<LogEntry>,
+    known: HashMap<UnitId, KnownUnit>,
+    visible: bool,
+    /// Only entries from this side's turns are shown, if set.
+    filter: Option<Side>,
+    /// How many of the newest shown entries are scrolled out of view.
+    scroll: usize,
+    /// The index in `entries` of the one under the mouse.
+    hovered: Option<usize>,
+    rect: Rect,
+    font: PixelFont,
+}
+
+impl CombatLog {
+    pub fn new(font: PixelFont, screen_w: u32, screen_h: u32) -> Self {
+        CombatLog {
+            entries: Vec::new(),
+            known: HashMap::new(),
+            visible: false,
+            filter: None,
+            scroll: 0,
+            hovered: None,
+            rect: Rect::new(screen_w as f32 - WIDTH, 0.0, WIDTH, screen_h as f32),
+            font,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.known.clear();
+        self.scroll = 0;
+        self.hovered = None;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.hovered = None;
+    }
+
+    /// Notes down the units on the field, so the ones that die before their
+    /// events are logged can still be named.
+    pub fn remember(&mut self, battle: &Battle) {
+        for (id, unit) in battle.units().iter_with_ids() {
+            self.known.insert(*id, KnownUnit::of(unit));
+        }
+    }
+
+    pub fn add(&mut self, battle: &Battle, turn: u32, side: Side, event: &BattleEvent) {
+        self.remember(battle);
+        let (text, units) = self.describe(event);
+        let entry = LogEntry { turn, side, text, units };
+        // Keep the view where it is if the player has scrolled back.
+        if self.scroll > 0 && self.shows(&entry) {
+            self.scroll += 1;
+        }
+        self.entries.push(entry);
+    }
+
+    fn name(&self, unit: UnitId) -> String {
+        match self.known.get(&unit) {
+            Some(known) => unit_name(known.class, known.side),
+            None => format!("unit {}", unit),
+        }
+    }
+
+    /// "(8/10)", or just "(8)" for a unit that was never seen.
+    fn health(&self, unit: UnitId, remaining: i16) -> String {
+        match self.known.get(&unit) {
+            Some(known) => format!("({}/{})", remaining.max(0), known.max_health),
+            None => format!("({})", remaining.max(0)),
+        }
+    }
+
+    fn describe(&self, event: &BattleEvent) -> (String, Vec<UnitId>) {
+        match *event {
+            BattleEvent::UnitMoved { unit, to, .. } => {
+                (format!("{} moves to {},{}", self.name(unit), to.x(), to.y()), vec![unit])
+            }
+            BattleEvent::UnitDamaged { attacker, target, report } => {
+                let mut text = format!("{} hits {} for {}", self.name(attacker), self.name(target), report.dealt);
+                if report.absorbed > 0 {
+                    text += &format!(", {} absorbed", report.absorbed);
+                }
+                text += &format!(" {}", self.health(target, report.remaining));
+                (text, vec![attacker, target])
+            }
+            BattleEvent::UnitDied { unit } => (format!("{} dies", self.name(unit)), vec![unit]),
+            BattleEvent::TurnEnded { side, .. } => (format!("{} turn ends", side.name()), Vec::new()),
+            BattleEvent::EffectApplied { unit, effect } => (
+                format!("{} gets {} {} for {} turns", self.name(unit), effect.kind, effect.magnitude, effect.turns),
+                vec![unit],
+            ),
+            BattleEvent::EffectTicked { unit, kind, change, remaining } => (
+                format!("{} {} {:+} {}", self.name(unit), kind, change, self.health(unit, remaining)),
+                vec![unit],
+            ),
+            BattleEvent::EffectExpired { unit, kind } => {
+                (format!("{}'s {} wears off", self.name(unit), kind), vec![unit])
+            }
+            BattleEvent::AbilityUsed { unit, ability, target } => (
+                format!("{} uses {} on {},{}", self.name(unit), ability, target.x(), target.y()),
+                vec![unit],
+            ),
+            BattleEvent::UnitHealed { healer, target, amount, shielded, remaining } => {
+                let mut text = format!("{} heals {} for {}", self.name(healer), self.name(target), amount);
+                if shielded > 0 {
+                    text += &format!(", {} shield", shielded);
+                }
+                text += &format!(" {}", self.health(target, remaining));
+                (text, vec![healer, target])
+            }
+            BattleEvent::UnitRaised { unit, tile } => {
+                (format!("{} rises at {},{}", self.name(unit), tile.x(), tile.y()), vec![unit])
+            }
+            BattleEvent::BattleOver { winner, objective } => {
+                (format!("{} wins: {}", winner.name(), objective), Vec::new())
+            }
+        }
+    }
+
+    fn shows(&self, entry: &LogEntry) -> bool {
+        self.filter.map_or(true, |side| entry.side == side)
+    }
+
+    /// Indices of the entries the filter lets through, oldest first.
+    fn shown(&self) -> Vec<usize> {
+        (0..self.entries.len()).filter(|index| self.shows(&self.entries[*index])).collect()
+    }
+
+    pub fn cycle_filter(&mut self) {
+        self.filter = match self.filter {
+            None => Some(Side::Player),
+            Some(Side::Player) => Some(Side::Enemy),
+            Some(Side::Enemy) => None,
+        };
+        self.scroll = 0;
+        self.hovered = None;
+    }
+
+    fn rows(&self) -> usize {
+        ((self.rect.h - 2.0 * PADDING) / LINE_HEIGHT) as usize - 1
+    }
+
+    /// Scrolls back through older entries for positive `by`.
+    pub fn scroll(&mut self, by: isize) {
+        let most = self.shown().len().saturating_sub(self.rows()) as isize;
+        self.scroll = (self.scroll as isize + by).max(0).min(most) as usize;
+        self.hovered = None;
+    }
+
+    /// The entries on screen, top to bottom.
+    fn on_screen(&self) -> Vec<usize> {
+        let shown = self.shown();
+        let end = shown.len() - self.scroll.min(shown.len());
+        let start = end.saturating_sub(self.rows());
+        shown[start..end].to_vec()
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.visible && self.rect.contains(Point2::new(x as f32, y as f32))
+    }
+
+    fn header_y(&self) -> f32 {
+        self.rect.y + PADDING
+    }
+
+    fn filter_label(&self) -> String {
+        format!("filter: {}", self.filter.map_or("all", |side| side.name()))
+    }
+
+    pub fn button_at(&self, x: i32, y: i32) -> Option<LogButton> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        let (x, y) = (x as f32, y as f32);
+        if y >= self.header_y() + LINE_HEIGHT {
+            return None;
+        }
+        let export_x = self.rect.x + self.rect.w - PADDING - self.font.width("export", 1.0);
+        let filter_end = self.rect.x + PADDING + self.font.width(&self.filter_label(), 1.0);
+        if x >= export_x {
+            Some(LogButton::Export)
+        } else if x <= filter_end {
+            Some(LogButton::Filter)
+        } else {
+            None
+        }
+    }
+
+    pub fn mouse_motion(&mut self, x: i32, y: i32) {
+        self.hovered = None;
+        if !self.contains(x, y) {
+            return;
+        }
+        let row = (y as f32 - self.header_y()) / LINE_HEIGHT - 1.0;
+        if row >= 0.0 {
+            self.hovered = self.on_screen().get(row as usize).cloned();
+        }
+    }
+
+    /// The units in the entry under the mouse.
+    pub fn highlighted(&self) -> &[UnitId] {
+        match self.hovered {
+            Some(index) => &self.entries[index].units,
+            None => &[],
+        }
+    }
+
+    /// Writes out every entry, whatever the filter.
+    pub fn export(&self, path: &Path) -> io::Result<()> {
+        let mut file = File::create(path)?;
+        for entry in &self.entries {
+            writeln!(file, "{}", entry)?;
+        }
+        Ok(())
+    }
+
+    /// Cuts the text short with ".." if it's wider than `width`.
+    fn fit(&self, text: &str, width: f32) -> String {
+        if self.font.width(text, 1.0) <= width {
+            return text.to_owned();
+        }
+        let mut fitted = text.to_owned();
+        while !fitted.is_empty() && self.font.width(&fitted, 1.0) + self.font.width("..", 1.0) > width {
+            fitted.pop();
+        }
+        fitted + ".."
+    }
+
+    pub fn render(&self, ctx: &mut Context, palette: Palette) {
+        if !self.visible {
+            return;
+        }
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.8)).unwrap();
+        graphics::rectangle(ctx, DrawMode::Fill, self.rect).unwrap();
+        let left = self.rect.x + PADDING;
+        let mut y = self.header_y();
+        graphics::set_color(ctx, Color::new(0.8, 0.6, 0.1, 1.0)).unwrap();
+        self.font.draw(ctx, &self.filter_label(), Point2::new(left, y), 1.0).unwrap();
+        let export_x = self.rect.x + self.rect.w - PADDING - self.font.width("export", 1.0);
+        self.font.draw(ctx, "export", Point2::new(export_x, y), 1.0).unwrap();
+        for index in self.on_screen() {
+            y += LINE_HEIGHT;
+            let entry = &self.entries[index];
+            if self.hovered == Some(index) {
+                graphics::set_color(ctx, Color::new(1.0, 1.0, 1.0, 0.2)).unwrap();
+                let rect = Rect::new(self.rect.x, y - 1.0, self.rect.w, LINE_HEIGHT);
+                graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+            }
+            graphics::set_color(ctx, side_color(palette, entry.side)).unwrap();
+            let text = self.fit(&entry.text, self.rect.w - 2.0 * PADDING);
+            self.font.draw(ctx, &text, Point2::new(left, y), 1.0).unwrap();
+        }
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+}
@@ -1,76 +1,250 @@
-use std::collections::HashMap;
-use ggez::graphics::Point2;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use ggez::graphics::{Point2, Rect};
 use ggez::Context;
 use ggez::graphics;
-use ggez::graphics::Image;
+use main_state::pixel_font::PixelFont;
 
 const SEPERATOR: &str = ":";
+const DEFAULT_GROUP: &str = "";
+const HISTORY_LENGTH: usize = 60;
+const LINE_HEIGHT: f32 = 7.0;
+const SPARKLINE_HEIGHT: f32 = 5.0;
+const SPARKLINE_PADDING: f32 = 2.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DebugOrder {
+    Insertion,
+    Sorted,
+}
+
+// rolling window of samples for numeric entries
+struct History {
+    samples: VecDeque<f32>,
+}
+
+impl History {
+    fn new() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(HISTORY_LENGTH),
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        if self.samples.len() == HISTORY_LENGTH {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(value);
+    }
+
+    fn latest(&self) -> f32 {
+        *self.samples.back().unwrap_or(&0.0)
+    }
+
+    fn min(&self) -> f32 {
+        self.samples.iter().cloned().fold(::std::f32::INFINITY, f32::min)
+    }
+
+    fn max(&self) -> f32 {
+        self.samples.iter().cloned().fold(::std::f32::NEG_INFINITY, f32::max)
+    }
+
+    fn avg(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f32>() / self.samples.len() as f32
+    }
+}
+
+enum DebugValue {
+    Text(String),
+    Number(History),
+}
+
+impl DebugValue {
+    fn to_string(&self) -> String {
+        match *self {
+            DebugValue::Text(ref text) => text.clone(),
+            DebugValue::Number(ref history) => format!(
+                "{:.1} ({:.1}/{:.1}/{:.1})",
+                history.latest(),
+                history.min(),
+                history.avg(),
+                history.max()
+            ),
+        }
+    }
+}
+
+struct DebugEntry {
+    label: String,
+    value: DebugValue,
+    refreshed: Instant,
+}
+
+struct DebugGroup {
+    name: String,
+    entries: Vec<DebugEntry>,
+}
+
+impl DebugGroup {
+    fn entry_mut(&mut self, label: &str) -> Option<&mut DebugEntry> {
+        self.entries.iter_mut().find(|entry| entry.label == label)
+    }
+}
 
 pub struct DebugTable {
     position: Point2,
-    data: HashMap<String, String>,
-    images: HashMap<String, Image>,
+    groups: Vec<DebugGroup>,
+    font: PixelFont,
+    order: DebugOrder,
+    expiry: Option<Duration>,
 }
 
 impl DebugTable {
-    pub fn new(ctx: &mut Context, position: Point2) -> DebugTable {
-        let mut t = DebugTable { 
+    pub fn new(font: PixelFont, position: Point2) -> DebugTable {
+        DebugTable {
             position,
-            data: HashMap::new(),
-            images: HashMap::new(),
-        };
-        let colon_image = DebugTable::make_image_from(ctx, SEPERATOR);
-        t.images.insert(SEPERATOR.to_owned(), colon_image);
-        t
+            groups: Vec::new(),
+            font,
+            order: DebugOrder::Insertion,
+            expiry: None,
+        }
+    }
+
+    pub fn set_order(&mut self, order: DebugOrder) {
+        self.order = order;
     }
+
+    /// Entries that are not reloaded within `expiry` are dropped from the table.
+    pub fn set_expiry(&mut self, expiry: Option<Duration>) {
+        self.expiry = expiry;
+    }
+
     pub fn load(&mut self, label: String, data: String) {
-        self.data.insert(label, data); 
+        self.load_text(DEFAULT_GROUP, &label[..], data);
     }
 
-    pub fn render(&mut self, ctx: &mut Context) {
-        let mut cursor = self.position.clone();
-        let mut lables = Vec::new();
-        for label in self.data.keys() {
-            lables.push(label.clone());
+    pub fn load_text(&mut self, group: &str, label: &str, data: String) {
+        let entry = self.entry(group, label, || DebugValue::Text(String::new()));
+        entry.value = DebugValue::Text(data);
+    }
+
+    pub fn load_number(&mut self, group: &str, label: &str, value: f32) {
+        let entry = self.entry(group, label, || DebugValue::Number(History::new()));
+        match entry.value {
+            DebugValue::Number(ref mut history) => history.push(value),
+            DebugValue::Text(_) => {
+                let mut history = History::new();
+                history.push(value);
+                entry.value = DebugValue::Number(history);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, group: &str, label: &str) {
+        for debug_group in self.groups.iter_mut().filter(|g| g.name == group) {
+            debug_group.entries.retain(|entry| entry.label != label);
         }
-        for label in lables {
-            self.draw_text(ctx, &label[..], &mut cursor, false);
-            self.draw_text(ctx, SEPERATOR, &mut cursor, false);
-            let value = &self.data.get(&label).unwrap().clone()[..];
-            self.draw_text(ctx, value, &mut cursor, true);
-            cursor.x = self.position.x;
+    }
+
+    fn entry<F: FnOnce() -> DebugValue>(&mut self, group: &str, label: &str, value: F) -> &mut DebugEntry {
+        let group_index = match self.groups.iter().position(|g| g.name == group) {
+            Some(index) => index,
+            None => {
+                self.groups.push(DebugGroup {
+                    name: group.to_owned(),
+                    entries: Vec::new(),
+                });
+                self.groups.len() - 1
+            }
+        };
+        let debug_group = &mut self.groups[group_index];
+        if debug_group.entry_mut(label).is_none() {
+            debug_group.entries.push(DebugEntry {
+                label: label.to_owned(),
+                value: value(),
+                refreshed: Instant::now(),
+            });
         }
+        let entry = debug_group.entry_mut(label).unwrap();
+        entry.refreshed = Instant::now();
+        entry
     }
 
-    fn draw_text(&mut self, ctx: &mut Context, string: &str, cursor: &mut Point2, new_line: bool) {
-        if let Some(image) = self.images.get(string) {
-            DebugTable::draw(ctx, image, cursor, new_line);
-            return
+    fn remove_expired(&mut self) {
+        if let Some(expiry) = self.expiry {
+            for group in self.groups.iter_mut() {
+                group.entries.retain(|entry| entry.refreshed.elapsed() < expiry);
+            }
         }
-        self.images.insert(String::from(string), DebugTable::make_image_from(ctx, string));
-        if let Some(image) = self.images.get(string) {
-            DebugTable::draw(ctx, image, cursor, new_line);
+        self.groups.retain(|group| !group.entries.is_empty());
+    }
+
+    fn ordered_indices(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut group_indices: Vec<usize> = (0..self.groups.len()).collect();
+        if self.order == DebugOrder::Sorted {
+            group_indices.sort_by_key(|&i| &self.groups[i].name);
         }
+        group_indices
+            .into_iter()
+            .map(|i| {
+                let entries = &self.groups[i].entries;
+                let mut entry_indices: Vec<usize> = (0..entries.len()).collect();
+                if self.order == DebugOrder::Sorted {
+                    entry_indices.sort_by_key(|&j| &entries[j].label);
+                }
+                (i, entry_indices)
+            })
+            .collect()
     }
 
-    fn draw(ctx: &mut Context, image: &Image, cursor: &mut Point2, new_line: bool) {
-        graphics::draw_ex(
-            ctx,
-            image,
-            graphics::DrawParam {
-                dest: *cursor,
-                ..Default::default()
-            },
-        ).unwrap();
-        cursor.x += image.width() as f32;
+    pub fn render(&mut self, ctx: &mut Context) {
+        self.remove_expired();
+        let mut cursor = self.position.clone();
+        for (group_index, entry_indices) in self.ordered_indices() {
+            let name = self.groups[group_index].name.clone();
+            if name != DEFAULT_GROUP {
+                self.draw_text(ctx, &format!("({})", name)[..], &mut cursor, true);
+                cursor.x = self.position.x;
+            }
+            for entry_index in entry_indices {
+                let (label, value) = {
+                    let entry = &self.groups[group_index].entries[entry_index];
+                    (entry.label.clone(), entry.value.to_string())
+                };
+                self.draw_text(ctx, &label[..], &mut cursor, false);
+                self.draw_text(ctx, SEPERATOR, &mut cursor, false);
+                self.draw_text(ctx, &value[..], &mut cursor, false);
+                if let DebugValue::Number(ref history) = self.groups[group_index].entries[entry_index].value {
+                    DebugTable::draw_sparkline(ctx, history, cursor);
+                }
+                cursor.x = self.position.x;
+                cursor.y += SPARKLINE_HEIGHT.max(LINE_HEIGHT);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, ctx: &mut Context, string: &str, cursor: &mut Point2, new_line: bool) {
+        self.font.draw(ctx, string, *cursor, 1.0).unwrap();
+        cursor.x += self.font.width(string, 1.0);
         if new_line {
-            cursor.y += image.height() as f32;
+            cursor.y += LINE_HEIGHT;
         }
     }
 
-    fn make_image_from(ctx: &mut Context, string: &str) -> Image {
-        graphics::Text::new(ctx, string, &graphics::Font::default_font().unwrap())
-            .unwrap()
-            .into_inner()
+    fn draw_sparkline(ctx: &mut Context, history: &History, cursor: Point2) {
+        let (min, max) = (history.min(), history.max());
+        let range = if max > min { max - min } else { 1.0 };
+        let left = cursor.x + SPARKLINE_PADDING;
+        for (i, sample) in history.samples.iter().enumerate() {
+            let height = 1.0 + (sample - min) / range * (SPARKLINE_HEIGHT - 1.0);
+            graphics::rectangle(
+                ctx,
+                graphics::DrawMode::Fill,
+                Rect::new(left + i as f32, cursor.y + SPARKLINE_HEIGHT - height, 1.0, height),
+            ).unwrap();
+        }
     }
 }
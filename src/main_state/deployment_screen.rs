@@ -0,0 +1,185 @@
+use battle::Battle;
+use deployment::DeploymentPlan;
+use game_object::*;
+use ggez::event::Keycode;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::Context;
+use main_state::overlay::render_deployment_zone;
+use main_state::pixel_font::PixelFont;
+use roster::Roster;
+use scenario::Scenario;
+
+const PANEL_WIDTH: f32 = 160.0;
+const PADDING: f32 = 3.0;
+const LINE_HEIGHT: f32 = 7.0;
+/// Veterans listed at once, one for each number key.
+const PAGE_SIZE: usize = 9;
+/// Lines above the roster in the panel.
+const HEADER_LINES: usize = 2;
+
+/// The phase before a campaign battle where the player picks veterans
+/// and places them in the scenario's deployment zone.
+pub struct DeploymentScreen {
+    pub node: String,
+    pub scenario: Scenario,
+    pub plan: DeploymentPlan,
+    pub seed: u64,
+    /// The veteran the next click on the zone places.
+    selected: Option<usize>,
+    /// Which `PAGE_SIZE` veterans of the roster are listed.
+    page: usize,
+    font: PixelFont,
+}
+
+impl DeploymentScreen {
+    pub fn new(node: &str, scenario: Scenario, plan: DeploymentPlan, seed: u64, font: PixelFont) -> Self {
+        Self {
+            node: node.to_owned(),
+            scenario,
+            plan,
+            seed,
+            selected: None,
+            page: 0,
+            font,
+        }
+    }
+
+    /// Number keys pick veterans in the order they're listed on the page.
+    pub fn index_from_key(keycode: Keycode) -> Option<usize> {
+        let keys = [
+            Keycode::Num1,
+            Keycode::Num2,
+            Keycode::Num3,
+            Keycode::Num4,
+            Keycode::Num5,
+            Keycode::Num6,
+            Keycode::Num7,
+            Keycode::Num8,
+            Keycode::Num9,
+        ];
+        keys.iter().position(|key| *key == keycode)
+    }
+
+    pub fn select(&mut self, roster: &Roster, index: usize) {
+        if index < roster.veterans.len() {
+            self.selected = Some(index);
+        }
+    }
+
+    /// Picks the veteran listed `row`th on the current page.
+    pub fn select_on_page(&mut self, roster: &Roster, row: usize) {
+        self.select(roster, self.page * PAGE_SIZE + row);
+    }
+
+    fn pages(roster: &Roster) -> usize {
+        ((roster.veterans.len() + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+
+    /// Moves `by` pages through the roster, wrapping around at the ends.
+    pub fn turn_page(&mut self, roster: &Roster, by: isize) {
+        let pages = DeploymentScreen::pages(roster) as isize;
+        self.page = ((self.page as isize + by) % pages + pages) as usize % pages as usize;
+    }
+
+    fn panel_rect(&self, roster: &Roster, screen_w: u32) -> Rect {
+        let height = self.lines(roster).len() as f32 * LINE_HEIGHT + 2.0 * PADDING;
+        Rect::new(screen_w as f32 - PANEL_WIDTH, 0.0, PANEL_WIDTH, height)
+    }
+
+    /// Selects the veteran listed under a click on the panel. Returns
+    /// whether the click landed on the panel at all.
+    pub fn click_panel(&mut self, roster: &Roster, screen_w: u32, x: i32, y: i32) -> bool {
+        let rect = self.panel_rect(roster, screen_w);
+        if !rect.contains(Point2::new(x as f32, y as f32)) {
+            return false;
+        }
+        let line = ((y as f32 - rect.y - PADDING) / LINE_HEIGHT).max(0.0) as usize;
+        if line >= HEADER_LINES && line - HEADER_LINES < self.page_len(roster) {
+            self.select_on_page(roster, line - HEADER_LINES);
+        }
+        true
+    }
+
+    fn page_len(&self, roster: &Roster) -> usize {
+        roster.veterans.len().saturating_sub(self.page * PAGE_SIZE).min(PAGE_SIZE)
+    }
+
+    /// The battle as it would start with the current plan.
+    pub fn preview(&self, roster: &Roster) -> Battle {
+        roster.deploy(&self.scenario, &self.plan, self.seed).0
+    }
+
+    /// Places the selected veteran on `tile`, or picks up the one already
+    /// standing there.
+    pub fn click_tile(&mut self, roster: &Roster, tile: Tile) -> Result<(), String> {
+        match self.selected {
+            Some(index) => {
+                self.plan.place(roster, index, tile).map_err(|e| e.to_string())?;
+                self.selected = None;
+            }
+            None => self.selected = self.plan.veteran_at(tile),
+        }
+        Ok(())
+    }
+
+    /// Sends whoever stands on `tile` back to the roster.
+    pub fn remove_at(&mut self, tile: Tile) {
+        if let Some(index) = self.plan.veteran_at(tile) {
+            self.plan.remove(index);
+        }
+        self.selected = None;
+    }
+
+    fn lines(&self, roster: &Roster) -> Vec<String> {
+        let budget = match self.plan.budget() {
+            Some(budget) => budget.to_string(),
+            None => "-".to_owned(),
+        };
+        let mut lines = vec![
+            format!("deploy for {}", self.scenario.name),
+            format!("points {} / {}", self.plan.cost(roster), budget),
+        ];
+        let listed = roster.veterans.iter().enumerate().skip(self.page * PAGE_SIZE).take(PAGE_SIZE);
+        for (row, (index, veteran)) in listed.enumerate() {
+            let marker = if self.selected == Some(index) {
+                "+"
+            } else if self.plan.tile_of(index).is_some() {
+                "*"
+            } else {
+                " "
+            };
+            lines.push(format!(
+                "{}{} {} {} lv{} ({} pts)",
+                marker,
+                row + 1,
+                veteran.name,
+                veteran.class.name(),
+                veteran.level,
+                veteran.cost()
+            ));
+        }
+        let pages = DeploymentScreen::pages(roster);
+        if pages > 1 {
+            lines.push(format!("page {}/{}, PageUp/PageDown", self.page + 1, pages));
+        }
+        lines.push("click or press a number to pick".to_owned());
+        lines.push("click places, right click removes".to_owned());
+        lines.push("Enter fights, Esc goes back".to_owned());
+        lines
+    }
+
+    pub fn render(&mut self, ctx: &mut Context, roster: &Roster, screen_w: u32) {
+        render_deployment_zone(ctx, self.plan.zone());
+        let lines = self.lines(roster);
+        let rect = self.panel_rect(roster, screen_w);
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.75)).unwrap();
+        graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        let mut cursor = Point2::new(rect.x + PADDING, rect.y + PADDING);
+        for line in &lines {
+            self.font.draw(ctx, line, cursor, 1.0).unwrap();
+            cursor.y += LINE_HEIGHT;
+        }
+    }
+}
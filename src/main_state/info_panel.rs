@@ -0,0 +1,134 @@
+use game_object::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::Context;
+use main_state::combat_log::unit_name;
+use main_state::overlay::side_color;
+use main_state::pixel_font::PixelFont;
+use map::Terrain;
+use settings::Palette;
+
+const WIDTH: f32 = 110.0;
+const LINE_HEIGHT: f32 = 7.0;
+const PADDING: f32 = 3.0;
+/// Size of a square in the attack pattern preview.
+const CELL: f32 = 4.0;
+const PATTERN_COLOR: (f32, f32, f32, f32) = (1.0, 0.9, 0.2, 1.0);
+
+/// The numbers behind a unit, in a panel at the bottom left, and what a
+/// terrain tile costs, next to it.
+pub struct InfoPanel {
+    screen_w: u32,
+    screen_h: u32,
+    font: PixelFont,
+}
+
+impl InfoPanel {
+    pub fn new(font: PixelFont, screen_w: u32, screen_h: u32) -> Self {
+        InfoPanel { screen_w, screen_h, font }
+    }
+
+    fn unit_lines(unit: &Unit) -> Vec<String> {
+        let health = unit.get_health();
+        let mut lines = vec![
+            format!("{} level {}", unit.get_side().name(), unit.get_level()),
+            format!("hp {}/{}", health.get_current().max(0), health.get_max()),
+        ];
+        if health.get_shield() > 0 || health.get_armor() > 0 {
+            lines.push(format!("shield {} armor {}", health.get_shield(), health.get_armor()));
+        }
+        lines.push(with_base("move", unit.get_movement(), unit.get_base_movement()));
+        lines.push(format!(
+            "{} {}",
+            with_base("damage", unit.get_damage(), unit.get_base_damage()),
+            unit.get_damage_type()
+        ));
+        for slot in unit.get_abilities() {
+            if slot.is_ready() {
+                lines.push(format!("- {}: ready", slot.ability.name));
+            } else {
+                lines.push(format!("- {}: {} turns", slot.ability.name, slot.cooldown_left));
+            }
+        }
+        for effect in unit.get_effects().iter() {
+            lines.push(format!("* {} {}, {} turns", effect.kind, effect.magnitude, effect.turns));
+        }
+        lines
+    }
+
+    /// Draws the panel for `unit`, with its attack pattern underneath.
+    pub fn render_unit(&self, ctx: &mut Context, unit: &Unit, palette: Palette) {
+        let lines = InfoPanel::unit_lines(unit);
+        let pattern = unit.get_attack_pattern();
+        let reach = pattern
+            .iter()
+            .map(|offset| offset.x().abs().max(offset.y().abs()))
+            .max()
+            .unwrap_or(0);
+        let grid = (2 * reach + 1) as f32 * CELL;
+        let height = (lines.len() + 1) as f32 * LINE_HEIGHT + grid + 3.0 * PADDING;
+        let rect = Rect::new(0.0, self.screen_h as f32 - height, WIDTH, height);
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.8)).unwrap();
+        graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+
+        let mut cursor = Point2::new(rect.x + PADDING, rect.y + PADDING);
+        graphics::set_color(ctx, side_color(palette, unit.get_side())).unwrap();
+        self.font.draw(ctx, &unit_name(unit.get_class(), unit.get_side()), cursor, 1.0).unwrap();
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        for line in &lines {
+            cursor.y += LINE_HEIGHT;
+            self.font.draw(ctx, line, cursor, 1.0).unwrap();
+        }
+
+        // The unit sits in the middle square, with every tile it can hit around it.
+        let top = cursor.y + LINE_HEIGHT + PADDING;
+        for y in -reach..reach + 1 {
+            for x in -reach..reach + 1 {
+                let color = if x == 0 && y == 0 {
+                    graphics::WHITE
+                } else if pattern.contains(&Tile::new(x as i16, y as i16)) {
+                    let (r, g, b, a) = PATTERN_COLOR;
+                    Color::new(r, g, b, a)
+                } else {
+                    Color::new(0.25, 0.25, 0.25, 1.0)
+                };
+                graphics::set_color(ctx, color).unwrap();
+                let cell = Rect::new(
+                    cursor.x + (x + reach) as f32 * CELL,
+                    top + (y + reach) as f32 * CELL,
+                    CELL - 1.0,
+                    CELL - 1.0,
+                );
+                graphics::rectangle(ctx, DrawMode::Fill, cell).unwrap();
+            }
+        }
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+
+    /// A tooltip at `position` saying what the tile costs to walk onto,
+    /// kept on screen.
+    pub fn render_terrain(&self, ctx: &mut Context, terrain: Terrain, position: Point2) {
+        let text = match terrain.move_cost() {
+            Some(cost) => format!("{}: costs {} to enter", terrain.name(), cost),
+            None => format!("{}: impassable", terrain.name()),
+        };
+        let width = self.font.width(&text, 1.0) + 2.0 * PADDING;
+        let x = position.x.min(self.screen_w as f32 - width);
+        let y = position.y.min(self.screen_h as f32 - LINE_HEIGHT - PADDING);
+        let rect = Rect::new(x, y, width, LINE_HEIGHT + PADDING);
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.8)).unwrap();
+        graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        let text_position = Point2::new(x + PADDING, y + PADDING - 1.0);
+        self.font.draw(ctx, &text, text_position, 1.0).unwrap();
+    }
+}
+
+/// "move 3", or "move 2 (3)" when effects have changed it from `base`.
+fn with_base(label: &str, value: u16, base: u16) -> String {
+    if value == base {
+        format!("{} {}", label, value)
+    } else {
+        format!("{} {} ({})", label, value, base)
+    }
+}
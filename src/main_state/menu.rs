@@ -0,0 +1,279 @@
+use event_bus::GameEvent;
+use ggez::event::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::{Context, GameResult};
+use main_state::options::OptionsScene;
+use main_state::pixel_font::PixelFont;
+use main_state::MainState;
+use scenario;
+use scene::{Scene, Transition};
+use settings::Settings;
+
+const LINE_HEIGHT: f32 = 8.0;
+const PADDING: f32 = 4.0;
+const WIDTH: f32 = 128.0;
+
+/// A column of options picked with the arrow keys and Enter, or a click.
+pub struct Choices {
+    labels: Vec<String>,
+    enabled: Vec<bool>,
+    selected: usize,
+    position: Point2,
+    font: PixelFont,
+    events: Vec<GameEvent>,
+}
+
+impl Choices {
+    pub fn new(labels: Vec<String>, position: Point2, font: PixelFont) -> Self {
+        let enabled = vec![true; labels.len()];
+        Self {
+            labels,
+            enabled,
+            selected: 0,
+            position,
+            font,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn set_label(&mut self, index: usize, label: String) {
+        self.labels[index] = label;
+    }
+
+    /// Disabled options are drawn greyed out and skipped over.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.enabled[index] = enabled;
+        if !enabled && self.selected == index {
+            self.step(1);
+        }
+    }
+
+    fn step(&mut self, by: isize) {
+        let count = self.labels.len() as isize;
+        let mut index = self.selected as isize;
+        for _ in 0..count {
+            index = (index + by + count) % count;
+            if self.enabled[index as usize] {
+                self.selected = index as usize;
+                return;
+            }
+        }
+    }
+
+    /// Moves the selection, returning the option chosen if any.
+    pub fn key_down(&mut self, keycode: Keycode) -> Option<usize> {
+        match keycode {
+            Keycode::Up => self.step(-1),
+            Keycode::Down => self.step(1),
+            Keycode::Return => {
+                self.events.push(GameEvent::MenuSelected);
+                return Some(self.selected);
+            }
+            _ => (),
+        }
+        None
+    }
+
+    /// The option under the screen position, if it can be chosen.
+    pub fn click(&mut self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = (x as f32 - self.position.x, y as f32 - self.position.y - PADDING);
+        if x < 0.0 || x > WIDTH || y < 0.0 {
+            return None;
+        }
+        let index = (y / LINE_HEIGHT) as usize;
+        if index < self.labels.len() && self.enabled[index] {
+            self.selected = index;
+            self.events.push(GameEvent::MenuSelected);
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Events since the last call, for the scene to hand on to the stack.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.events.drain(..).collect()
+    }
+
+    pub fn render(&mut self, ctx: &mut Context) {
+        let height = self.labels.len() as f32 * LINE_HEIGHT + 2.0 * PADDING;
+        let rect = Rect::new(self.position.x, self.position.y, WIDTH, height);
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.8)).unwrap();
+        graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+        let mut cursor = Point2::new(self.position.x + PADDING, self.position.y + PADDING);
+        for (index, label) in self.labels.iter().enumerate() {
+            let color = if !self.enabled[index] {
+                Color::new(0.4, 0.4, 0.4, 1.0)
+            } else if index == self.selected {
+                Color::new(0.8, 0.6, 0.1, 1.0)
+            } else {
+                graphics::WHITE
+            };
+            let marker = if index == self.selected { "*" } else { " " };
+            graphics::set_color(ctx, color).unwrap();
+            self.font.draw(ctx, &format!("{} {}", marker, label), cursor, 1.0).unwrap();
+            cursor.y += LINE_HEIGHT;
+        }
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MenuItem {
+    NewCampaign,
+    Skirmish,
+    Load,
+    Options,
+    Quit,
+}
+
+impl MenuItem {
+    fn all() -> Vec<MenuItem> {
+        vec![
+            MenuItem::NewCampaign,
+            MenuItem::Skirmish,
+            MenuItem::Load,
+            MenuItem::Options,
+            MenuItem::Quit,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            MenuItem::NewCampaign => "new campaign",
+            MenuItem::Skirmish => "skirmish",
+            MenuItem::Load => "continue campaign",
+            MenuItem::Options => "options",
+            MenuItem::Quit => "quit",
+        }
+    }
+
+    fn index(&self) -> usize {
+        MenuItem::all().iter().position(|item| item == self).unwrap()
+    }
+}
+
+/// The main menu, where every game starts from.
+pub struct MainMenu {
+    screen_w: u32,
+    screen_h: u32,
+    choices: Choices,
+    /// Scenarios a skirmish can be fought on, picked with left and right.
+    scenarios: Vec<String>,
+    scenario: usize,
+    message: Option<String>,
+    settings: Settings,
+    font: PixelFont,
+}
+
+impl MainMenu {
+    pub fn new(ctx: &mut Context, screen_w: u32, screen_h: u32, settings: Settings, font: PixelFont) -> Self {
+        let labels = MenuItem::all().iter().map(|item| item.name().to_owned()).collect();
+        let position = Point2::new((screen_w as f32 - WIDTH) / 2.0, screen_h as f32 / 3.0);
+        let mut menu = Self {
+            screen_w,
+            screen_h,
+            choices: Choices::new(labels, position, font.clone()),
+            scenarios: scenario::names(),
+            scenario: 0,
+            message: None,
+            settings,
+            font,
+        };
+        menu.choices.set_enabled(MenuItem::Load.index(), MainState::save_path(ctx).is_file());
+        menu.choices.set_enabled(MenuItem::Skirmish.index(), !menu.scenarios.is_empty());
+        menu.update_skirmish_label();
+        menu
+    }
+
+    fn update_skirmish_label(&mut self) {
+        if let Some(name) = self.scenarios.get(self.scenario) {
+            let label = format!("skirmish: ( {} )", name);
+            self.choices.set_label(MenuItem::Skirmish.index(), label);
+        }
+    }
+
+    fn cycle_scenario(&mut self, by: usize) {
+        if !self.scenarios.is_empty() {
+            self.scenario = (self.scenario + by) % self.scenarios.len();
+            self.update_skirmish_label();
+        }
+    }
+
+    fn choose(&mut self, ctx: &mut Context, index: usize) -> Transition {
+        let (w, h) = (self.screen_w, self.screen_h);
+        let (settings, font) = (self.settings.clone(), self.font.clone());
+        let game = match MenuItem::all()[index] {
+            MenuItem::NewCampaign => MainState::campaign(ctx, w, h, settings, font, true),
+            MenuItem::Load => MainState::campaign(ctx, w, h, settings, font, false),
+            MenuItem::Skirmish => MainState::skirmish(ctx, w, h, settings, font, &self.scenarios[self.scenario][..]),
+            MenuItem::Options => return Transition::Replace(Box::new(OptionsScene::new(w, h, settings, font))),
+            MenuItem::Quit => return Transition::Quit,
+        };
+        match game {
+            Ok(game) => Transition::Replace(Box::new(game)),
+            Err(e) => {
+                self.message = Some(format!("error: {}", e));
+                Transition::None
+            }
+        }
+    }
+}
+
+impl Scene for MainMenu {
+    fn music(&self) -> Option<&'static str> {
+        Some("menu")
+    }
+
+    fn notify(&mut self, event: &GameEvent) {
+        if let GameEvent::Warning(ref warning) = *event {
+            self.message = Some(warning.clone());
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        self.choices.take_events()
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.choices.render(ctx);
+        if let Some(ref message) = self.message {
+            graphics::set_color(ctx, graphics::WHITE)?;
+            let position = Point2::new(PADDING, self.screen_h as f32 - LINE_HEIGHT - PADDING);
+            self.font.draw(ctx, message, position, 1.0)?;
+        }
+        Ok(())
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod) -> Transition {
+        let skirmish = self.choices.selected() == MenuItem::Skirmish.index();
+        match keycode {
+            Keycode::Escape => Transition::Quit,
+            Keycode::Left if skirmish => {
+                let back = self.scenarios.len().saturating_sub(1);
+                self.cycle_scenario(back);
+                Transition::None
+            }
+            Keycode::Right if skirmish => {
+                self.cycle_scenario(1);
+                Transition::None
+            }
+            _ => match self.choices.key_down(keycode) {
+                Some(index) => self.choose(ctx, index),
+                None => Transition::None,
+            },
+        }
+    }
+
+    fn mouse_button_down(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) -> Transition {
+        match self.choices.click(x, y) {
+            Some(index) if button == MouseButton::Left => self.choose(ctx, index),
+            _ => Transition::None,
+        }
+    }
+}
@@ -1,58 +1,579 @@
-use std::time::Duration;
-use std::thread;
+use std::fs;
+use std::path::PathBuf;
+use ai;
+use ai::profile::scenario_ai;
+use ai::{Ai, AiProfile, GreedyAi, Timing};
 use assets::{SpriteName, Sprites};
+use battle::{Battle, Command};
+use campaign::DEFAULT_CAMPAIGN;
+use console::{CommandRegistry, Console};
+use event_bus::GameEvent;
 use game_object::*;
+use map::fog::FogOfWar;
+use rand;
+use scenario;
+use scenario::Scenario;
+use scene::{Scene, Transition};
+use settings::{Control, Settings};
+use simulation::GameRecord;
+use viewport::PIXELS_PER_TILE;
 use ggez::event::*;
 use ggez::graphics;
-use ggez::graphics::Image;
-use ggez::graphics::{Point2, Vector2};
+use ggez::graphics::Point2;
 use ggez::timer;
-use ggez::{Context, GameResult};
+use ggez::{Context, GameError, GameResult};
 
-pub static PIXELS_PER_TILE: u32 = 8;
-pub static SCALE_FACTOR: f32 = 8.0;
-pub fn screen_pixels_per_tile() -> u32 {
-    (PIXELS_PER_TILE as f32 * SCALE_FACTOR) as u32
-}
+const FOG_DIMMING: f32 = 0.6;
+/// Seconds the enemy waits before playing its turn at normal speed.
+const ENEMY_TURN_DELAY: f32 = 0.5;
+const SAVE_FILE: &str = "campaign.txt";
+const LOG_FILE: &str = "combat_log.txt";
 
+mod action_menu;
+mod campaign_map;
+mod combat_log;
 mod debug;
+mod deployment_screen;
+mod info_panel;
 mod input;
+mod menu;
+mod options;
+mod overlay;
+mod pause;
+mod pixel_font;
+mod results_screen;
+mod title;
+
+use self::action_menu::{ActionMenu, MenuChoice};
+use self::campaign_map::{CampaignMap, CampaignRun};
+use self::combat_log::{CombatLog, LogButton, SCROLL_STEP};
+use self::deployment_screen::DeploymentScreen;
+use self::info_panel::InfoPanel;
+use self::pause::PauseScene;
+use self::results_screen::ResultsScreen;
+use self::overlay::{render_ability_targets, render_attack_range, render_highlights, render_status_icons, HeatMap};
+pub use self::menu::MainMenu;
+pub use self::pixel_font::PixelFont;
+pub use self::title::{TitleScene, GAME_TITLE};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Screen {
+    Battle,
+    CampaignMap,
+    Deployment,
+}
 
 pub struct MainState {
     screen_w: u32,
     screen_h: u32,
     sprites: Sprites,
+    font: PixelFont,
     debug_display: debug::DebugTable,
-    units: Units,
-    current_turn: Side,
+    console: Console,
+    battle: Battle,
+    enemy_profile: AiProfile,
+    heat_map: HeatMap,
+    selected: Option<UnitId>,
+    menu_choice: MenuChoice,
+    action_menu: ActionMenu,
+    fog: FogOfWar,
+    screen: Screen,
+    campaign: Option<CampaignRun>,
+    campaign_map: CampaignMap,
+    deployment: Option<DeploymentScreen>,
+    /// Plays the enemy in a skirmish. Campaign battles bring their own.
+    enemy: Option<Box<dyn Ai>>,
+    /// What has happened in the battle being fought, for the results.
+    record: Option<GameRecord>,
+    settings: Settings,
+    /// Seconds the enemy has waited so far to play its turn.
+    enemy_wait: f32,
+    combat_log: CombatLog,
+    info_panel: InfoPanel,
+    /// The tile under the mouse, unless it's over a panel.
+    hovered_tile: Option<Tile>,
+    /// Taken from the battle but not yet by the stack.
+    events: Vec<GameEvent>,
 }
 
 impl MainState {
-    pub fn new(ctx: &mut Context, screen_w: u32, screen_h: u32) -> GameResult<MainState> {
+    /// A sandbox with a few units and nobody to fight.
+    pub fn new(ctx: &mut Context, screen_w: u32, screen_h: u32, settings: Settings, font: PixelFont) -> GameResult<MainState> {
+        Ok(MainState::with_battle(ctx, screen_w, screen_h, settings, font, Battle::new(0)).init())
+    }
+
+    /// A one-off battle on a scenario against its AI.
+    pub fn skirmish(
+        ctx: &mut Context,
+        screen_w: u32,
+        screen_h: u32,
+        settings: Settings,
+        font: PixelFont,
+        name: &str,
+    ) -> GameResult<MainState> {
+        let seed = rand::random();
+        let scenario = Scenario::load(name).map_err(|e| GameError::UnknownError(e.to_string()))?;
+        let battle = scenario.to_battle(seed);
+        let enemy = match scenario_ai(&scenario, Side::Enemy, &battle, seed.wrapping_add(1), Timing::Interactive) {
+            Ok(Some(ai)) => ai,
+            Ok(None) => Box::new(GreedyAi::new(seed.wrapping_add(1))),
+            Err(e) => return Err(GameError::UnknownError(e)),
+        };
+        let mut state = MainState::with_battle(ctx, screen_w, screen_h, settings, font, battle);
+        state.enemy = Some(enemy);
+        Ok(state)
+    }
+
+    /// The campaign map, either starting over or continuing the save.
+    pub fn campaign(
+        ctx: &mut Context,
+        screen_w: u32,
+        screen_h: u32,
+        settings: Settings,
+        font: PixelFont,
+        fresh: bool,
+    ) -> GameResult<MainState> {
+        let run = if fresh {
+            CampaignRun::start_new(DEFAULT_CAMPAIGN)
+        } else {
+            CampaignRun::load_or_start(&MainState::save_path(ctx), DEFAULT_CAMPAIGN)
+        };
+        let mut state = MainState::with_battle(ctx, screen_w, screen_h, settings, font, Battle::new(0));
+        state.campaign = Some(run.map_err(|e| GameError::UnknownError(e.to_string()))?);
+        state.screen = Screen::CampaignMap;
+        if fresh {
+            state.save_campaign(ctx);
+        }
+        Ok(state)
+    }
+
+    fn with_battle(
+        ctx: &mut Context,
+        screen_w: u32,
+        screen_h: u32,
+        settings: Settings,
+        font: PixelFont,
+        battle: Battle,
+    ) -> MainState {
         graphics::set_default_filter(ctx, graphics::FilterMode::Nearest);
         graphics::set_background_color(ctx, graphics::Color::new(0.0, 0.0, 0.0, 1.0));
-        let state = MainState {
+        MainState {
             screen_w,
             screen_h,
             sprites: Sprites::new(ctx),
-            debug_display: debug::DebugTable::new(ctx, Point2::new(0.0, 0.0)),
-            units: Units::new()
-        }.init();
-        Ok(state)
+            font: font.clone(),
+            debug_display: debug::DebugTable::new(font.clone(), Point2::new(0.0, 0.0)),
+            console: Console::new(MainState::command_registry(), font.clone()),
+            battle,
+            enemy_profile: AiProfile::find("normal").unwrap_or_default(),
+            heat_map: HeatMap::Off,
+            selected: None,
+            menu_choice: MenuChoice::Attack,
+            action_menu: ActionMenu::new(font.clone()),
+            fog: FogOfWar::new(Side::Player),
+            screen: Screen::Battle,
+            campaign: None,
+            campaign_map: CampaignMap::new(font.clone()),
+            deployment: None,
+            enemy: None,
+            record: None,
+            settings,
+            enemy_wait: 0.0,
+            combat_log: CombatLog::new(font.clone(), screen_w, screen_h),
+            info_panel: InfoPanel::new(font, screen_w, screen_h),
+            hovered_tile: None,
+            events: Vec::new(),
+        }
+    }
+
+    fn command_registry() -> CommandRegistry {
+        let mut registry = CommandRegistry::with_builtins();
+        ai::register_commands(&mut registry);
+        scenario::register_commands(&mut registry);
+        registry
     }
 
     fn init(mut self) -> Self {
-        self.units.make_unit(Unit::warrior(Tile::new(3, 3), Side::Player));
-        self.units.make_unit(Unit::archer(Tile::new(5, 3), Side::Player));
+        self.battle.set_fog_of_war(true);
+        self.battle.units_mut().make_unit(Unit::warrior(Tile::new(3, 3), Side::Player));
+        self.battle.units_mut().make_unit(Unit::archer(Tile::new(5, 3), Side::Player));
+        self.battle.units_mut().make_unit(Unit::monk(Tile::new(4, 4), Side::Player));
         self
     }
 
+    fn save_path(ctx: &Context) -> PathBuf {
+        ctx.filesystem.get_user_data_dir().join(SAVE_FILE)
+    }
+
+    /// Shows the campaign map, continuing the saved campaign if there is one.
+    fn open_campaign(&mut self, ctx: &Context) {
+        if self.campaign.is_none() {
+            match CampaignRun::load_or_start(&MainState::save_path(ctx), DEFAULT_CAMPAIGN) {
+                Ok(run) => self.campaign = Some(run),
+                Err(e) => {
+                    self.console.print(&format!("error: {}", e)[..]);
+                    return;
+                }
+            }
+        }
+        self.screen = Screen::CampaignMap;
+    }
+
+    fn save_campaign(&mut self, ctx: &Context) {
+        let path = MainState::save_path(ctx);
+        let result = match self.campaign {
+            Some(ref run) => fs::create_dir_all(ctx.filesystem.get_user_data_dir())
+                .map_err(|e| e.to_string())
+                .and_then(|_| run.save.save(&path).map_err(|e| e.to_string())),
+            None => return,
+        };
+        if let Err(e) = result {
+            self.console.print(&format!("error: could not save campaign: {}", e)[..]);
+        }
+    }
+
+    /// Goes to the deployment phase for the battle at `node`.
+    fn begin_deployment(&mut self, node: &str) {
+        let seed = self.battle.seed().wrapping_add(self.battle.turn_number() as u64);
+        let result = match self.campaign {
+            Some(ref run) => run.prepare(node),
+            None => return,
+        };
+        match result {
+            Ok((scenario, plan)) => {
+                self.deployment = Some(DeploymentScreen::new(node, scenario, plan, seed, self.font.clone()));
+                self.refresh_deployment_preview();
+                self.selected = None;
+                self.screen = Screen::Deployment;
+            }
+            Err(e) => self.console.print(&format!("error: {}", e)[..]),
+        }
+    }
+
+    /// Shows the battle as the current deployment plan would start it.
+    fn refresh_deployment_preview(&mut self) {
+        if let (Some(run), Some(screen)) = (self.campaign.as_ref(), self.deployment.as_ref()) {
+            self.battle = screen.preview(&run.save.roster);
+            self.fog = FogOfWar::new(Side::Player);
+        }
+    }
+
+    /// Checks the plan and starts the battle with it.
+    fn confirm_deployment(&mut self) {
+        let result = match (self.campaign.as_mut(), self.deployment.as_ref()) {
+            (Some(run), Some(screen)) => run.start_battle(&screen.node[..], &screen.scenario, &screen.plan, screen.seed),
+            _ => return,
+        };
+        match result {
+            Ok(battle) => {
+                self.battle = battle;
+                self.fog = FogOfWar::new(Side::Player);
+                self.selected = None;
+                self.menu_choice = MenuChoice::Attack;
+                self.deployment = None;
+                self.combat_log.clear();
+                self.screen = Screen::Battle;
+            }
+            Err(e) => self.console.print(&format!("error: {}", e)[..]),
+        }
+    }
+
+    fn deployment_key_down(&mut self, keycode: Keycode) {
+        match keycode {
+            Keycode::Escape => {
+                self.deployment = None;
+                self.screen = Screen::CampaignMap;
+            }
+            Keycode::Return => self.confirm_deployment(),
+            _ => if let (Some(run), Some(screen)) = (self.campaign.as_ref(), self.deployment.as_mut()) {
+                match keycode {
+                    Keycode::PageUp => screen.turn_page(&run.save.roster, -1),
+                    Keycode::PageDown => screen.turn_page(&run.save.roster, 1),
+                    _ => if let Some(row) = DeploymentScreen::index_from_key(keycode) {
+                        screen.select_on_page(&run.save.roster, row);
+                    },
+                }
+            },
+        }
+    }
+
+    fn click_deployment(&mut self, button: MouseButton, tile: Tile, x: i32, y: i32) {
+        let screen_w = self.screen_w;
+        let result = match (self.campaign.as_ref(), self.deployment.as_mut()) {
+            (Some(run), Some(screen)) => match button {
+                MouseButton::Left if screen.click_panel(&run.save.roster, screen_w, x, y) => Ok(()),
+                MouseButton::Left => screen.click_tile(&run.save.roster, tile),
+                MouseButton::Right => {
+                    screen.remove_at(tile);
+                    Ok(())
+                }
+                _ => Ok(()),
+            },
+            _ => return,
+        };
+        if let Err(e) = result {
+            self.console.print(&format!("error: {}", e)[..]);
+        }
+        self.refresh_deployment_preview();
+    }
+
+    /// Starts keeping a record once both sides are on the field, plays the
+    /// enemy, and shows the results once it's over.
+    fn update_battle(&mut self, ctx: &Context) -> Transition {
+        let both_sides = !self.battle.units().ids_of(Side::Player).is_empty()
+            && !self.battle.units().ids_of(Side::Enemy).is_empty();
+        if self.record.is_none() && both_sides && !self.battle.is_over() {
+            self.record = Some(GameRecord::start(&self.battle));
+        }
+        self.update_enemy(ctx);
+        self.collect_events();
+        if !self.battle.is_over() {
+            return Transition::None;
+        }
+        let fought = self.campaign.as_ref().map(|run| run.battle.is_some()) == Some(true);
+        let messages = if fought {
+            let messages = self.campaign.as_mut().unwrap().finish_battle(&self.battle);
+            self.save_campaign(ctx);
+            Some(messages)
+        } else {
+            None
+        };
+        self.selected = None;
+        if fought {
+            self.screen = Screen::CampaignMap;
+        }
+        match self.record.take() {
+            Some(mut record) => {
+                record.finish(&self.battle, self.battle.turn_number());
+                let campaign = messages.as_ref().map(|messages| &messages[..]);
+                Transition::Push(Box::new(ResultsScreen::new(&self.battle, &record, campaign, self.screen_w, self.font.clone())))
+            }
+            None => Transition::None,
+        }
+    }
+
+    /// Plays the enemy's turn once it has waited a moment, with the campaign
+    /// battle's AI if one is being fought.
+    fn update_enemy(&mut self, ctx: &Context) {
+        if self.battle.current_turn() != Side::Enemy || self.battle.is_over() {
+            return;
+        }
+        self.enemy_wait += timer::duration_to_f64(timer::get_delta(ctx)) as f32 * self.settings.animation_speed;
+        if self.enemy_wait < ENEMY_TURN_DELAY {
+            return;
+        }
+        self.enemy_wait = 0.0;
+        match self.campaign.as_mut().and_then(|run| run.battle.as_mut()) {
+            Some(fought) => ai::play_turn(&mut *fought.enemy, &mut self.battle),
+            None => if let Some(ref mut enemy) = self.enemy {
+                ai::play_turn(&mut **enemy, &mut self.battle);
+            },
+        }
+    }
+
+    /// Takes what has happened in the battle, keeping it for the results and
+    /// the campaign battle being fought until the stack takes it to publish.
+    fn collect_events(&mut self) {
+        let events = self.battle.take_events();
+        for event in &events {
+            if let GameEvent::Battle { side, turn, ref event } = *event {
+                if let Some(ref mut record) = self.record {
+                    record.add(turn, side, event.clone());
+                }
+                if let Some(fought) = self.campaign.as_mut().and_then(|run| run.battle.as_mut()) {
+                    fought.events.push(event.clone());
+                }
+            }
+        }
+        self.events.extend(events);
+    }
+
+    fn campaign_key_down(&mut self, ctx: &mut Context, keycode: Keycode) -> Transition {
+        match keycode {
+            Keycode::Escape => {
+                // Back to the battle being fought, if there is one.
+                let fighting = self.campaign.as_ref().map(|run| run.battle.is_some()) == Some(true);
+                if !fighting {
+                    return Transition::Push(Box::new(PauseScene::new(self.screen_w, self.screen_h, self.settings.clone(), self.font.clone())));
+                }
+                self.screen = Screen::Battle;
+            }
+            Keycode::N => {
+                match CampaignRun::start_new(DEFAULT_CAMPAIGN) {
+                    Ok(run) => self.campaign = Some(run),
+                    Err(e) => self.console.print(&format!("error: {}", e)[..]),
+                }
+                self.save_campaign(ctx);
+            }
+            Keycode::R => {
+                let result = match self.campaign {
+                    Some(ref mut run) => run.recruit(UnitClass::Warrior),
+                    None => return Transition::None,
+                };
+                match result {
+                    Ok(message) => self.console.print(&message[..]),
+                    Err(e) => self.console.print(&format!("error: {}", e)[..]),
+                }
+                self.save_campaign(ctx);
+            }
+            _ => (),
+        }
+        Transition::None
+    }
+
+    fn click_campaign_map(&mut self, x: i32, y: i32) {
+        let node = match self.campaign {
+            Some(ref run) if run.battle.is_none() => CampaignMap::node_at(&run.campaign, x as f32, y as f32),
+            _ => None,
+        };
+        if let Some(node) = node {
+            self.begin_deployment(&node[..]);
+        }
+    }
+
+    /// Draws the terrain the player has seen, dimming what is out of sight.
+    fn draw_map(&self, ctx: &mut Context) {
+        let map = self.battle.map();
+        let size = PIXELS_PER_TILE as f32;
+        for tile in map.tiles() {
+            if !self.fog.is_explored(tile) {
+                continue;
+            }
+            self.draw_sprite(ctx, map.terrain(tile).get_sprite(), tile.x(), tile.y());
+            if !self.fog.is_visible(tile) {
+                graphics::set_color(ctx, graphics::Color::new(0.0, 0.0, 0.0, FOG_DIMMING)).unwrap();
+                let rect = graphics::Rect::new(tile.x() as f32 * size, tile.y() as f32 * size, size, size);
+                graphics::rectangle(ctx, graphics::DrawMode::Fill, rect).unwrap();
+                graphics::set_color(ctx, graphics::WHITE).unwrap();
+            }
+        }
+    }
+
     fn draw_units(&self, ctx: &mut Context) {
-        for unit in self.units.iter() {
-            self.draw_object(ctx, unit);
+        for unit in self.battle.units().iter() {
+            if unit.get_side() == Side::Player || self.fog.is_visible(unit.get_tile()) {
+                self.draw_object(ctx, unit);
+                render_status_icons(ctx, unit, self.settings.palette);
+            }
+        }
+    }
+
+    /// Outlines the units in the log entry under the mouse that the player
+    /// can see.
+    fn draw_log_highlights(&self, ctx: &mut Context) {
+        let tiles: Vec<Tile> = self.combat_log
+            .highlighted()
+            .iter()
+            .filter_map(|unit_id| self.battle.units().get_unit(*unit_id))
+            .filter(|unit| unit.get_side() == Side::Player || self.fog.is_visible(unit.get_tile()))
+            .map(|unit| unit.get_tile())
+            .collect();
+        render_highlights(ctx, &tiles);
+    }
+
+    /// Shows the unit under the mouse, or else the selected one, and what
+    /// the terrain under the mouse costs if there's no unit on it.
+    fn draw_info(&self, ctx: &mut Context) {
+        let seen = |tile: Tile| self.battle.in_bounds(tile) && self.fog.is_explored(tile);
+        let hovered_unit = self.hovered_tile
+            .filter(|tile| self.fog.is_visible(*tile))
+            .and_then(|tile| self.battle.units().unit_at(tile));
+        if let Some(unit) = hovered_unit.or(self.selected).and_then(|unit_id| self.battle.units().get_unit(unit_id)) {
+            self.info_panel.render_unit(ctx, unit, self.settings.palette);
+        }
+        if let Some(tile) = self.hovered_tile.filter(|tile| hovered_unit.is_none() && seen(*tile)) {
+            let size = PIXELS_PER_TILE as f32;
+            let position = Point2::new((tile.x() + 1) as f32 * size, (tile.y() + 1) as f32 * size);
+            self.info_panel.render_terrain(ctx, self.battle.map().terrain(tile), position);
+        }
+    }
+
+    fn click_combat_log(&mut self, ctx: &Context, x: i32, y: i32) {
+        match self.combat_log.button_at(x, y) {
+            Some(LogButton::Filter) => self.combat_log.cycle_filter(),
+            Some(LogButton::Export) => {
+                let dir = ctx.filesystem.get_user_data_dir();
+                let path = dir.join(LOG_FILE);
+                let result = fs::create_dir_all(dir).and_then(|_| self.combat_log.export(&path));
+                match result {
+                    Ok(()) => self.console.print(&format!("combat log written to {}", path.display())[..]),
+                    Err(e) => self.console.print(&format!("error: could not write combat log: {}", e)[..]),
+                }
+            }
+            None => (),
         }
     }
 
+    fn draw_action_menu(&mut self, ctx: &mut Context) {
+        let unit_id = match self.selected {
+            Some(unit_id) if self.controls(unit_id) => unit_id,
+            _ => return,
+        };
+        let tile = self.battle.units().get_unit(unit_id).unwrap().get_tile();
+        let size = PIXELS_PER_TILE as f32;
+        let position = Point2::new((tile.x() + 1) as f32 * size, tile.y() as f32 * size);
+        self.action_menu
+            .render(ctx, &self.battle, unit_id, self.menu_choice, position);
+    }
+
+    /// Whether the player can give orders to the unit right now.
+    fn controls(&self, unit_id: UnitId) -> bool {
+        self.battle.current_turn() == Side::Player
+            && self.battle.units().get_unit(unit_id).map(|unit| unit.get_side()) == Some(Side::Player)
+    }
+
+    /// Turns a click on `tile` into an order for the selected unit, if it
+    /// makes sense as one.
+    /// The battle as the player knows it under fog of war, for queries that
+    /// would otherwise give hidden enemies away.
+    fn fog_view(&self) -> Option<Battle> {
+        if self.battle.fog_of_war() {
+            Some(self.fog.view(&self.battle))
+        } else {
+            None
+        }
+    }
+
+    fn order_for(&self, unit_id: UnitId, tile: Tile) -> Option<Command> {
+        if !self.controls(unit_id) {
+            return None;
+        }
+        let view = self.fog_view();
+        let known = view.as_ref().unwrap_or(&self.battle);
+        if let MenuChoice::Ability(ability) = self.menu_choice {
+            return if known.ability_targets(unit_id, ability).contains(&tile) {
+                Some(Command::UseAbility { unit: unit_id, ability, target: tile })
+            } else {
+                None
+            };
+        }
+        if let Some(target) = known.units().unit_at(tile) {
+            if self.fog.is_visible(tile) && known.attack_targets(unit_id).contains(&target) {
+                return Some(Command::Attack { unit: unit_id, target });
+            }
+        }
+        if known.can_move(unit_id) && known.reachable_tiles(unit_id).contains(&tile) {
+            return Some(Command::Move { unit: unit_id, to: tile });
+        }
+        None
+    }
+
+    fn click_tile(&mut self, tile: Tile) {
+        if let Some(command) = self.selected.and_then(|unit_id| self.order_for(unit_id, tile)) {
+            if let Err(e) = self.battle.apply(command) {
+                self.console.print(&format!("error: {}", e)[..]);
+            }
+            self.menu_choice = MenuChoice::Attack;
+            return;
+        }
+        self.selected = if self.fog.is_visible(tile) {
+            self.battle.units().unit_at(tile)
+        } else {
+            None
+        };
+        self.menu_choice = MenuChoice::Attack;
+    }
+
     fn draw_object<T: HasSprite + HasTile>(&self, ctx: &mut Context, object: &T) {
         let sprite = object.get_sprite();
         self.draw_sprite(ctx, sprite, object.get_tile_x(), object.get_tile_y());
@@ -63,64 +584,180 @@ impl MainState {
             ctx,
             name,
             Point2::new(
-                (tile_x * screen_pixels_per_tile() as i32) as f32,
-                (tile_y * screen_pixels_per_tile() as i32) as f32,
+                (tile_x * PIXELS_PER_TILE as i32) as f32,
+                (tile_y * PIXELS_PER_TILE as i32) as f32,
             ),
         );
     }
 }
 
-impl EventHandler for MainState {
-    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        const DESIRED_FPS: u32 = 60;
-        while timer::check_update_time(ctx, DESIRED_FPS) {
-            let _seconds = 1.0 / (DESIRED_FPS as f32);
+impl Scene for MainState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<Transition> {
+        if self.screen == Screen::Battle {
+            self.combat_log.remember(&self.battle);
+            return Ok(self.update_battle(ctx));
         }
-        Ok(())
+        Ok(Transition::None)
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        //clear the contex
-        graphics::clear(ctx);
-
         //load the fps to the debug table
-        //let fps = timer::get_fps(ctx) as u16;
-        //self.debug_display.load("fps".to_owned(), fps.to_string());
+        let fps = timer::get_fps(ctx) as f32;
+        self.debug_display.load_number("perf", "fps", fps);
+        self.debug_display.load_text("ai", "enemy", self.enemy_profile.describe());
+        self.debug_display.load_text("ai", "heat map", self.heat_map.name());
 
-        //draw test
-        for x in 0..10 {
-            for y in 0..10 {
-                self.draw_sprite(ctx, SpriteName::Floor, x, y);
+        if self.screen == Screen::CampaignMap {
+            if let Some(ref run) = self.campaign {
+                self.campaign_map.render(ctx, run);
+            }
+            return self.console.render(ctx, self.screen_w);
+        }
+
+        self.fog.update(&self.battle);
+        self.draw_map(ctx);
+        if self.screen == Screen::Deployment {
+            self.draw_units(ctx);
+            if let (Some(run), Some(screen)) = (self.campaign.as_ref(), self.deployment.as_mut()) {
+                screen.render(ctx, &run.save.roster, self.screen_w);
+            }
+            return self.console.render(ctx, self.screen_w);
+        }
+        {
+            let overlaid = self.heat_map != HeatMap::Off || self.selected.is_some();
+            let view = if overlaid { self.fog_view() } else { None };
+            let known = view.as_ref().unwrap_or(&self.battle);
+            self.heat_map.render(ctx, known, self.settings.palette);
+            if let Some(unit_id) = self.selected {
+                match self.menu_choice {
+                    MenuChoice::Attack => render_attack_range(ctx, known, unit_id),
+                    MenuChoice::Ability(index) => render_ability_targets(ctx, known, unit_id, index),
+                }
             }
         }
 
         self.draw_units(ctx);
-        self.draw_sprite(ctx, SpriteName::Archer, 1, 1);
-        self.draw_sprite(ctx, SpriteName::Wizard, 1, 0);
-        self.draw_sprite(ctx, SpriteName::UndeadWizard, 0, 1);
-        self.draw_sprite(ctx, SpriteName::Bodyguard, 0, 0);
+        self.draw_log_highlights(ctx);
+        self.draw_action_menu(ctx);
+        self.draw_info(ctx);
+        self.combat_log.render(ctx, self.settings.palette);
 
-        //draw tmut he debug table
         self.debug_display.render(ctx);
+        self.console.render(ctx, self.screen_w)
+    }
 
-        //show context on screen
-        graphics::present(ctx);
+    fn key_down(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod) -> Transition {
+        let control = self.settings.keys.control(keycode);
+        if control == Some(Control::Console) {
+            self.console.toggle();
+            return Transition::None;
+        }
+        if self.console.is_open() {
+            if self.console.key_down(&mut self.battle, keycode) {
+                // Commands can swap or rearrange the battle under the record.
+                self.record = None;
+            }
+            return Transition::None;
+        }
+        match self.screen {
+            Screen::CampaignMap => return self.campaign_key_down(ctx, keycode),
+            Screen::Deployment => {
+                self.deployment_key_down(keycode);
+                return Transition::None;
+            }
+            Screen::Battle => (),
+        }
+        match control {
+            Some(Control::Pause) => return Transition::Push(Box::new(PauseScene::new(self.screen_w, self.screen_h, self.settings.clone(), self.font.clone()))),
+            Some(Control::HeatMap) => self.heat_map = self.heat_map.next(),
+            Some(Control::CombatLog) => self.combat_log.toggle(),
+            Some(Control::CampaignMap) => self.open_campaign(ctx),
+            Some(Control::EndTurn) => if self.battle.current_turn() == Side::Player {
+                let _ = self.battle.apply(Command::EndTurn);
+            },
+            _ => match keycode {
+                Keycode::PageUp => self.combat_log.scroll(SCROLL_STEP),
+                Keycode::PageDown => self.combat_log.scroll(-SCROLL_STEP),
+                _ => if let Some(choice) = MenuChoice::from_key(keycode) {
+                    self.menu_choice = choice;
+                },
+            },
+        }
+        Transition::None
+    }
 
-        //yeild cpu when not active
-        timer::yield_now();
-        Ok(())
+    fn music(&self) -> Option<&'static str> {
+        match self.screen {
+            Screen::Battle => Some("battle"),
+            Screen::CampaignMap | Screen::Deployment => Some("campaign"),
+        }
     }
 
-    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
-        match keycode {
-            Keycode::Escape => ctx.quit().unwrap(),
-            _ => (), // Do nothing
+    fn notify(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::Battle { side, turn, ref event } => self.combat_log.add(&self.battle, turn, side, event),
+            GameEvent::Warning(ref warning) => self.console.print(warning),
+            GameEvent::MenuSelected => (),
         }
     }
 
-    fn key_up_event(&mut self, _ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
-        match keycode {
-            _ => (), // Do nothing
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        self.collect_events();
+        self.events.drain(..).collect()
+    }
+
+    fn text_input(&mut self, _ctx: &mut Context, text: &str) {
+        if self.console.is_open() {
+            self.console.text_input(text);
+        }
+    }
+
+    fn mouse_motion(&mut self, _ctx: &mut Context, x: i32, y: i32) {
+        if self.screen != Screen::Battle {
+            return;
+        }
+        self.combat_log.mouse_motion(x, y);
+        let size = PIXELS_PER_TILE as i32;
+        self.hovered_tile = if self.combat_log.contains(x, y) {
+            None
+        } else {
+            Some(Tile::new((x / size) as i16, (y / size) as i16))
+        };
+    }
+
+    fn mouse_wheel(&mut self, _ctx: &mut Context, y: i32) {
+        if self.screen == Screen::Battle && self.combat_log.is_visible() {
+            self.combat_log.scroll(y.signum() as isize * SCROLL_STEP);
+        }
+    }
+
+    fn mouse_button_down(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) -> Transition {
+        let size = PIXELS_PER_TILE as i32;
+        let tile = Tile::new((x / size) as i16, (y / size) as i16);
+        match self.screen {
+            Screen::CampaignMap => {
+                if button == MouseButton::Left {
+                    self.click_campaign_map(x, y);
+                }
+                return Transition::None;
+            }
+            Screen::Deployment => {
+                self.click_deployment(button, tile, x, y);
+                return Transition::None;
+            }
+            Screen::Battle => (),
+        }
+        if self.combat_log.contains(x, y) {
+            if button == MouseButton::Left {
+                self.click_combat_log(ctx, x, y);
+            }
+            return Transition::None;
+        }
+        match button {
+            MouseButton::Left => self.click_tile(tile),
+            MouseButton::Right => self.selected = None,
+            _ => (),
         }
+        Transition::None
     }
 }
@@ -0,0 +1,254 @@
+use event_bus::GameEvent;
+use ggez::event::*;
+use ggez::graphics;
+use ggez::graphics::Point2;
+use ggez::{Context, GameResult};
+use main_state::menu::{Choices, MainMenu};
+use main_state::pixel_font::PixelFont;
+use scene::{Scene, Transition};
+use settings::*;
+
+const LEFT: f32 = 16.0;
+const TOP: f32 = 4.0;
+const LINE_HEIGHT: f32 = 8.0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum OptionItem {
+    Resolution,
+    Fullscreen,
+    UiScale,
+    Vsync,
+    AnimationSpeed,
+    Volume,
+    MusicVolume,
+    EffectsVolume,
+    Palette,
+    Key(Control),
+    Save,
+    Back,
+}
+
+impl OptionItem {
+    fn all() -> Vec<OptionItem> {
+        let mut items = vec![
+            OptionItem::Resolution,
+            OptionItem::Fullscreen,
+            OptionItem::UiScale,
+            OptionItem::Vsync,
+            OptionItem::AnimationSpeed,
+            OptionItem::Volume,
+            OptionItem::MusicVolume,
+            OptionItem::EffectsVolume,
+            OptionItem::Palette,
+        ];
+        items.extend(Control::all().into_iter().map(OptionItem::Key));
+        items.push(OptionItem::Save);
+        items.push(OptionItem::Back);
+        items
+    }
+}
+
+/// Edits a copy of the settings, which only replaces the saved ones once
+/// it's saved.
+pub struct OptionsScene {
+    screen_w: u32,
+    screen_h: u32,
+    /// The settings in use, handed back to the main menu.
+    saved: Settings,
+    /// Just saved and not yet picked up by the stack.
+    unapplied: Option<Settings>,
+    settings: Settings,
+    choices: Choices,
+    /// The control waiting for its new key, if any.
+    rebinding: Option<Control>,
+    message: Option<String>,
+    font: PixelFont,
+}
+
+impl OptionsScene {
+    pub fn new(screen_w: u32, screen_h: u32, settings: Settings, font: PixelFont) -> Self {
+        let labels = vec![String::new(); OptionItem::all().len()];
+        let position = Point2::new(LEFT, TOP);
+        let mut scene = Self {
+            screen_w,
+            screen_h,
+            saved: settings.clone(),
+            unapplied: None,
+            settings,
+            choices: Choices::new(labels, position, font.clone()),
+            rebinding: None,
+            message: Some("window changes take effect after a restart".to_owned()),
+            font,
+        };
+        scene.update_labels();
+        scene
+    }
+
+    fn label(&self, item: OptionItem) -> String {
+        let switch = |on: bool| if on { "on" } else { "off" };
+        let settings = &self.settings;
+        match item {
+            OptionItem::Resolution => format!("resolution: {}x{}", settings.resolution.0, settings.resolution.1),
+            OptionItem::Fullscreen => format!("fullscreen: {}", switch(settings.fullscreen)),
+            OptionItem::UiScale => format!("ui scale: up to {}x", settings.ui_scale),
+            OptionItem::Vsync => format!("vsync: {}", switch(settings.vsync)),
+            OptionItem::AnimationSpeed => format!("animation speed: {}x", settings.animation_speed),
+            OptionItem::Volume => format!("volume: {}%", settings.volume),
+            OptionItem::MusicVolume => format!("music: {}%", settings.music_volume),
+            OptionItem::EffectsVolume => format!("sound effects: {}%", settings.effects_volume),
+            OptionItem::Palette => format!("palette: {}", settings.palette.name()),
+            OptionItem::Key(control) if self.rebinding == Some(control) => format!("{}: press a key", control.name()),
+            OptionItem::Key(control) => format!("{}: {}", control.name(), settings.keys.key(control).name()),
+            OptionItem::Save => "save".to_owned(),
+            OptionItem::Back => "back".to_owned(),
+        }
+    }
+
+    fn update_labels(&mut self) {
+        for (index, item) in OptionItem::all().into_iter().enumerate() {
+            let label = self.label(item);
+            self.choices.set_label(index, label);
+        }
+    }
+
+    /// Steps the setting to its next or previous value.
+    fn adjust(&mut self, item: OptionItem, forward: bool) {
+        let settings = &mut self.settings;
+        match item {
+            OptionItem::Resolution => {
+                let index = RESOLUTIONS.iter().position(|r| *r == settings.resolution);
+                settings.resolution = RESOLUTIONS[cycle(index, RESOLUTIONS.len(), forward)];
+            }
+            OptionItem::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            OptionItem::UiScale => {
+                settings.ui_scale = if forward {
+                    (settings.ui_scale + 1).min(MAX_UI_SCALE)
+                } else {
+                    (settings.ui_scale - 1).max(1)
+                }
+            }
+            OptionItem::Vsync => settings.vsync = !settings.vsync,
+            OptionItem::AnimationSpeed => {
+                let index = ANIMATION_SPEEDS.iter().position(|speed| *speed == settings.animation_speed);
+                settings.animation_speed = ANIMATION_SPEEDS[cycle(index, ANIMATION_SPEEDS.len(), forward)];
+            }
+            OptionItem::Volume => step_volume(&mut settings.volume, forward),
+            OptionItem::MusicVolume => step_volume(&mut settings.music_volume, forward),
+            OptionItem::EffectsVolume => step_volume(&mut settings.effects_volume, forward),
+            OptionItem::Palette => {
+                let palettes = Palette::all();
+                let index = palettes.iter().position(|palette| *palette == settings.palette);
+                settings.palette = palettes[cycle(index, palettes.len(), forward)];
+            }
+            OptionItem::Key(_) | OptionItem::Save | OptionItem::Back => (),
+        }
+    }
+
+    fn choose(&mut self, ctx: &mut Context, item: OptionItem) -> Transition {
+        match item {
+            OptionItem::Key(control) => self.rebinding = Some(control),
+            OptionItem::Save => match self.settings.save(ctx.filesystem.get_user_config_dir()) {
+                Ok(()) => {
+                    self.saved = self.settings.clone();
+                    self.unapplied = Some(self.settings.clone());
+                    return self.back(ctx);
+                }
+                Err(e) => self.message = Some(format!("error: {}", e)),
+            },
+            OptionItem::Back => return self.back(ctx),
+            _ => self.adjust(item, true),
+        }
+        Transition::None
+    }
+
+    fn back(&self, ctx: &mut Context) -> Transition {
+        let menu = MainMenu::new(ctx, self.screen_w, self.screen_h, self.saved.clone(), self.font.clone());
+        Transition::Replace(Box::new(menu))
+    }
+}
+
+/// The index after (or before) `index` in a list of `len`, starting from
+/// the first when the current value isn't in the list.
+fn cycle(index: Option<usize>, len: usize, forward: bool) -> usize {
+    match index {
+        Some(index) if forward => (index + 1) % len,
+        Some(index) => (index + len - 1) % len,
+        None => 0,
+    }
+}
+
+fn step_volume(volume: &mut u32, up: bool) {
+    *volume = if up {
+        (*volume + 10).min(100)
+    } else {
+        volume.saturating_sub(10)
+    };
+}
+
+impl Scene for OptionsScene {
+    fn music(&self) -> Option<&'static str> {
+        Some("menu")
+    }
+
+    fn notify(&mut self, event: &GameEvent) {
+        if let GameEvent::Warning(ref warning) = *event {
+            self.message = Some(warning.clone());
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.choices.render(ctx);
+        if let Some(ref message) = self.message {
+            graphics::set_color(ctx, graphics::WHITE)?;
+            let position = Point2::new(LEFT, self.screen_h as f32 - 2.0 * LINE_HEIGHT);
+            self.font.draw(ctx, message, position, 1.0)?;
+        }
+        Ok(())
+    }
+
+    fn saved_settings(&mut self) -> Option<Settings> {
+        self.unapplied.take()
+    }
+
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        self.choices.take_events()
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod) -> Transition {
+        if let Some(control) = self.rebinding.take() {
+            self.settings.keys.bind(control, keycode);
+            self.update_labels();
+            return Transition::None;
+        }
+        let item = OptionItem::all()[self.choices.selected()];
+        let transition = match keycode {
+            Keycode::Escape => return self.back(ctx),
+            Keycode::Left => {
+                self.adjust(item, false);
+                Transition::None
+            }
+            Keycode::Right => {
+                self.adjust(item, true);
+                Transition::None
+            }
+            _ => match self.choices.key_down(keycode) {
+                Some(index) => self.choose(ctx, OptionItem::all()[index]),
+                None => Transition::None,
+            },
+        };
+        self.update_labels();
+        transition
+    }
+
+    fn mouse_button_down(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) -> Transition {
+        if self.rebinding.is_some() {
+            return Transition::None;
+        }
+        let transition = match self.choices.click(x, y) {
+            Some(index) if button == MouseButton::Left => self.choose(ctx, OptionItem::all()[index]),
+            _ => Transition::None,
+        };
+        self.update_labels();
+        transition
+    }
+}
@@ -0,0 +1,187 @@
+use ai::Analysis;
+use battle::Battle;
+use game_object::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Rect};
+use ggez::Context;
+use settings::Palette;
+use viewport::PIXELS_PER_TILE;
+
+const MAX_ALPHA: f32 = 0.6;
+const TARGET_COLOR: (f32, f32, f32, f32) = (1.0, 0.9, 0.2, 0.45);
+const BLOCKED_COLOR: (f32, f32, f32, f32) = (0.5, 0.3, 0.7, 0.45);
+const DEPLOYMENT_COLOR: (f32, f32, f32, f32) = (0.2, 0.6, 1.0, 0.35);
+
+/// Which tactical map, if any, is drawn over the floor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HeatMap {
+    Off,
+    Threat(Side),
+    Influence,
+}
+
+impl HeatMap {
+    pub fn next(&self) -> HeatMap {
+        match *self {
+            HeatMap::Off => HeatMap::Threat(Side::Enemy),
+            HeatMap::Threat(Side::Enemy) => HeatMap::Threat(Side::Player),
+            HeatMap::Threat(Side::Player) => HeatMap::Influence,
+            HeatMap::Influence => HeatMap::Off,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match *self {
+            HeatMap::Off => "off".to_owned(),
+            HeatMap::Threat(side) => format!("{} threat", side.name()),
+            HeatMap::Influence => "influence".to_owned(),
+        }
+    }
+
+    pub fn render(&self, ctx: &mut Context, battle: &Battle, palette: Palette) {
+        if *self == HeatMap::Off {
+            return;
+        }
+        let analysis = Analysis::new(battle);
+        let shades: Vec<(Tile, Color)> = match *self {
+            HeatMap::Off => Vec::new(),
+            HeatMap::Threat(side) => {
+                let map = analysis.threat(side);
+                let max = map.max().max(1) as f32;
+                map.tiles()
+                    .into_iter()
+                    .map(|tile| (tile, shade(palette, side, map.get(tile) as f32 / max)))
+                    .collect()
+            }
+            HeatMap::Influence => {
+                let map = analysis.influence();
+                let max = map.tiles()
+                    .into_iter()
+                    .map(|tile| map.get(tile).abs())
+                    .fold(1.0, f32::max);
+                map.tiles()
+                    .into_iter()
+                    .map(|tile| {
+                        let value = map.get(tile) / max;
+                        let side = if value >= 0.0 { Side::Player } else { Side::Enemy };
+                        (tile, shade(palette, side, value.abs()))
+                    })
+                    .collect()
+            }
+        };
+        for (tile, color) in shades {
+            if color.a > 0.0 {
+                fill_tile(ctx, tile, color);
+            }
+        }
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+    }
+}
+
+/// Highlights what a unit could shoot at from where it stands, with the
+/// tiles its attack pattern covers but can't see in a different colour.
+pub fn render_attack_range(ctx: &mut Context, battle: &Battle, unit_id: UnitId) {
+    let from = match battle.units().get_unit(unit_id) {
+        Some(unit) => unit.get_tile(),
+        None => return,
+    };
+    let (r, g, b, a) = TARGET_COLOR;
+    for tile in battle.attack_tiles_from(unit_id, from) {
+        fill_tile(ctx, tile, Color::new(r, g, b, a));
+    }
+    let (r, g, b, a) = BLOCKED_COLOR;
+    for tile in battle.blocked_attack_tiles_from(unit_id, from) {
+        fill_tile(ctx, tile, Color::new(r, g, b, a));
+    }
+    graphics::set_color(ctx, graphics::WHITE).unwrap();
+}
+
+/// Highlights where the unit could aim one of its abilities.
+pub fn render_ability_targets(ctx: &mut Context, battle: &Battle, unit_id: UnitId, index: usize) {
+    let (r, g, b, a) = TARGET_COLOR;
+    for tile in battle.ability_targets(unit_id, index) {
+        fill_tile(ctx, tile, Color::new(r, g, b, a));
+    }
+    graphics::set_color(ctx, graphics::WHITE).unwrap();
+}
+
+/// Highlights the tiles the player may deploy onto.
+pub fn render_deployment_zone(ctx: &mut Context, zone: &[Tile]) {
+    let (r, g, b, a) = DEPLOYMENT_COLOR;
+    for tile in zone {
+        fill_tile(ctx, *tile, Color::new(r, g, b, a));
+    }
+    graphics::set_color(ctx, graphics::WHITE).unwrap();
+}
+
+/// Outlines the tiles, such as where the units in a hovered log entry stand.
+pub fn render_highlights(ctx: &mut Context, tiles: &[Tile]) {
+    let size = PIXELS_PER_TILE as f32;
+    let (r, g, b, _) = TARGET_COLOR;
+    graphics::set_color(ctx, Color::new(r, g, b, 1.0)).unwrap();
+    for tile in tiles {
+        let rect = Rect::new(tile.x() as f32 * size, tile.y() as f32 * size, size, size);
+        graphics::rectangle(ctx, DrawMode::Line(1.0), rect).unwrap();
+    }
+    graphics::set_color(ctx, graphics::WHITE).unwrap();
+}
+
+/// Draws a small square for each status effect along the top of the unit's tile.
+pub fn render_status_icons(ctx: &mut Context, unit: &Unit, palette: Palette) {
+    let size = PIXELS_PER_TILE as f32;
+    let icon = size / 4.0;
+    let (x, y) = (unit.get_tile_x() as f32 * size, unit.get_tile_y() as f32 * size);
+    for (index, effect) in unit.get_effects().iter().enumerate() {
+        graphics::set_color(ctx, effect_color(palette, effect.kind)).unwrap();
+        let rect = Rect::new(x + index as f32 * icon, y, icon - 1.0, icon - 1.0);
+        graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+    }
+    graphics::set_color(ctx, graphics::WHITE).unwrap();
+}
+
+fn effect_color(palette: Palette, kind: EffectKind) -> Color {
+    if palette == Palette::ColourBlind {
+        return match kind {
+            EffectKind::Poison => Color::new(0.0, 0.6, 0.5, 1.0),
+            EffectKind::Regeneration => Color::new(0.8, 0.6, 0.7, 1.0),
+            EffectKind::Stun => Color::new(0.95, 0.9, 0.25, 1.0),
+            EffectKind::Ward => Color::new(0.35, 0.7, 0.9, 1.0),
+            EffectKind::Haste => Color::new(0.9, 0.6, 0.0, 1.0),
+            EffectKind::Weakness => Color::new(0.8, 0.4, 0.0, 1.0),
+        };
+    }
+    match kind {
+        EffectKind::Poison => Color::new(0.4, 0.9, 0.2, 1.0),
+        EffectKind::Regeneration => Color::new(1.0, 0.5, 0.7, 1.0),
+        EffectKind::Stun => Color::new(1.0, 1.0, 0.3, 1.0),
+        EffectKind::Ward => Color::new(0.5, 0.8, 1.0, 1.0),
+        EffectKind::Haste => Color::new(1.0, 0.6, 0.1, 1.0),
+        EffectKind::Weakness => Color::new(0.6, 0.4, 0.8, 1.0),
+    }
+}
+
+fn fill_tile(ctx: &mut Context, tile: Tile, color: Color) {
+    let size = PIXELS_PER_TILE as f32;
+    graphics::set_color(ctx, color).unwrap();
+    let rect = Rect::new(tile.x() as f32 * size, tile.y() as f32 * size, size, size);
+    graphics::rectangle(ctx, DrawMode::Fill, rect).unwrap();
+}
+
+/// The side's shade at full strength, for text and markers.
+pub fn side_color(palette: Palette, side: Side) -> Color {
+    let mut color = shade(palette, side, 1.0);
+    color.a = 1.0;
+    color
+}
+
+/// Blue for the player and red for the enemy, or orange with the colour
+/// blind palette, more opaque the stronger.
+fn shade(palette: Palette, side: Side, strength: f32) -> Color {
+    let alpha = strength.min(1.0) * MAX_ALPHA;
+    match (side, palette) {
+        (Side::Player, Palette::Standard) => Color::new(0.2, 0.4, 1.0, alpha),
+        (Side::Enemy, Palette::Standard) => Color::new(1.0, 0.2, 0.2, alpha),
+        (Side::Player, Palette::ColourBlind) => Color::new(0.0, 0.45, 0.7, alpha),
+        (Side::Enemy, Palette::ColourBlind) => Color::new(0.9, 0.6, 0.0, alpha),
+    }
+}
@@ -0,0 +1,83 @@
+use event_bus::GameEvent;
+use ggez::event::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::{Context, GameResult};
+use main_state::menu::{Choices, MainMenu};
+use main_state::pixel_font::PixelFont;
+use scene::{Scene, Transition};
+use settings::Settings;
+
+const WIDTH: f32 = 128.0;
+
+/// Stops the game underneath until it's resumed or left.
+pub struct PauseScene {
+    screen_w: u32,
+    screen_h: u32,
+    /// Handed back to the main menu if the game is left.
+    settings: Settings,
+    font: PixelFont,
+    choices: Choices,
+}
+
+impl PauseScene {
+    const RESUME: usize = 0;
+    const MAIN_MENU: usize = 1;
+    const QUIT: usize = 2;
+
+    pub fn new(screen_w: u32, screen_h: u32, settings: Settings, font: PixelFont) -> Self {
+        let labels = vec!["resume".to_owned(), "main menu".to_owned(), "quit".to_owned()];
+        let position = Point2::new((screen_w as f32 - WIDTH) / 2.0, screen_h as f32 / 3.0);
+        Self {
+            screen_w,
+            screen_h,
+            settings,
+            choices: Choices::new(labels, position, font.clone()),
+            font,
+        }
+    }
+
+    fn choose(&mut self, ctx: &mut Context, index: usize) -> Transition {
+        match index {
+            PauseScene::RESUME => Transition::Pop,
+            PauseScene::MAIN_MENU => Transition::Reset(Box::new(MainMenu::new(ctx, self.screen_w, self.screen_h, self.settings.clone(), self.font.clone()))),
+            PauseScene::QUIT => Transition::Quit,
+            _ => Transition::None,
+        }
+    }
+}
+
+impl Scene for PauseScene {
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let screen = Rect::new(0.0, 0.0, self.screen_w as f32, self.screen_h as f32);
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.5))?;
+        graphics::rectangle(ctx, DrawMode::Fill, screen)?;
+        self.choices.render(ctx);
+        Ok(())
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod) -> Transition {
+        if keycode == Keycode::Escape {
+            return Transition::Pop;
+        }
+        match self.choices.key_down(keycode) {
+            Some(index) => self.choose(ctx, index),
+            None => Transition::None,
+        }
+    }
+
+    fn mouse_button_down(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) -> Transition {
+        match self.choices.click(x, y) {
+            Some(index) if button == MouseButton::Left => self.choose(ctx, index),
+            _ => Transition::None,
+        }
+    }
+
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        self.choices.take_events()
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
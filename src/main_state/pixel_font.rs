@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use ggez::graphics;
+use ggez::graphics::{DrawParam, Image, Point2, Rect};
+use ggez::{Context, GameError, GameResult};
+use utils::find_resource;
+
+const FONT_FILE: &str = "fonts/pixel_font.txt";
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    Image(GameError),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FontError::Io(ref e) => write!(f, "could not read the font: {}", e),
+            FontError::Image(ref e) => write!(f, "could not load the font image: {}", e),
+            FontError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for FontError {
+    fn description(&self) -> &str {
+        "invalid font"
+    }
+}
+
+impl From<FontError> for GameError {
+    fn from(e: FontError) -> Self {
+        GameError::ResourceLoadError(e.to_string())
+    }
+}
+
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+/// Where one character sits on the font image, in pixels.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Glyph {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct FontConfig {
+    image: String,
+    height: u32,
+    space: u32,
+    spacing: u32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+impl FontConfig {
+    fn parse(text: &str) -> Result<FontConfig, FontError> {
+        let mut config = FontConfig::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            config
+                .parse_directive(&words)
+                .map_err(|message| FontError::Parse { line: index + 1, message })?;
+        }
+        Ok(config)
+    }
+
+    fn parse_directive(&mut self, words: &[&str]) -> Result<(), String> {
+        let number = |index: usize| -> Result<u32, String> {
+            words.get(index)
+                .and_then(|word| word.parse().ok())
+                .ok_or_else(|| format!("'{}' expects a number", words[0]))
+        };
+        match words[0] {
+            "image" => {
+                self.image = words.get(1).ok_or_else(|| "expected an image path".to_owned())?.to_string()
+            }
+            "height" => self.height = number(1)?,
+            "space" => self.space = number(1)?,
+            "spacing" => self.spacing = number(1)?,
+            "glyph" => {
+                let mut chars = words.get(1).map(|word| word.chars()).ok_or_else(|| "expected a character".to_owned())?;
+                let character = match (chars.next(), chars.next()) {
+                    (Some(character), None) => character,
+                    _ => return Err(format!("'{}' isn't a single character", words[1])),
+                };
+                let glyph = Glyph {
+                    x: number(2)?,
+                    y: number(3)?,
+                    width: number(4)?,
+                };
+                self.glyphs.insert(character, glyph);
+            }
+            directive => return Err(format!("unknown directive '{}'", directive)),
+        }
+        Ok(())
+    }
+}
+
+/// A variable-width bitmap font, drawn straight from its image in whatever
+/// colour is set.
+#[derive(Clone)]
+pub struct PixelFont {
+    image: Image,
+    config: FontConfig,
+}
+
+impl PixelFont {
+    pub fn load(ctx: &mut Context) -> Result<PixelFont, FontError> {
+        let path = find_resource(Path::new(FONT_FILE))
+            .ok_or_else(|| FontError::Io(io::Error::new(io::ErrorKind::NotFound, FONT_FILE)))?;
+        let mut text = String::new();
+        File::open(&path)?.read_to_string(&mut text)?;
+        let config = FontConfig::parse(&text[..])?;
+        let image = Image::new(ctx, &config.image[..]).map_err(FontError::Image)?;
+        Ok(PixelFont { image, config })
+    }
+
+    fn glyph(&self, character: char) -> Option<Glyph> {
+        let upper = character.to_ascii_uppercase();
+        self.config.glyphs.get(&character).or_else(|| self.config.glyphs.get(&upper)).cloned()
+    }
+
+    /// How far the character moves the cursor along. Characters the font
+    /// doesn't have take up a space.
+    fn advance(&self, character: char) -> u32 {
+        match self.glyph(character) {
+            Some(glyph) => glyph.width + self.config.spacing,
+            None => self.config.space + self.config.spacing,
+        }
+    }
+
+    pub fn width(&self, text: &str, scale: f32) -> f32 {
+        text.chars().map(|character| self.advance(character)).sum::<u32>() as f32 * scale
+    }
+
+    pub fn draw(&self, ctx: &mut Context, text: &str, position: Point2, scale: f32) -> GameResult<()> {
+        let bounds = Rect::new(0.0, 0.0, self.image.width() as f32, self.image.height() as f32);
+        let mut x = position.x;
+        for character in text.chars() {
+            if let Some(glyph) = self.glyph(character) {
+                let src = Rect::fraction(
+                    glyph.x as f32,
+                    glyph.y as f32,
+                    glyph.width as f32,
+                    self.config.height as f32,
+                    &bounds,
+                );
+                let param = DrawParam {
+                    src,
+                    dest: Point2::new(x, position.y),
+                    scale: Point2::new(scale, scale),
+                    ..Default::default()
+                };
+                graphics::draw_ex(ctx, &self.image, param)?;
+            }
+            x += self.advance(character) as f32 * scale;
+        }
+        Ok(())
+    }
+}
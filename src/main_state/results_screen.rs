@@ -0,0 +1,92 @@
+use battle::Battle;
+use game_object::*;
+use ggez::event::*;
+use ggez::graphics;
+use ggez::graphics::{Color, DrawMode, Point2, Rect};
+use ggez::{Context, GameResult};
+use main_state::pixel_font::PixelFont;
+use scene::{Scene, Transition};
+use simulation::GameRecord;
+use statistics::unit_records;
+
+const ORIGIN: (f32, f32) = (16.0, 8.0);
+const LINE_HEIGHT: f32 = 7.0;
+const PADDING: f32 = 4.0;
+
+/// How a battle ended and how every unit in it fared.
+pub struct ResultsScreen {
+    screen_w: u32,
+    lines: Vec<String>,
+    font: PixelFont,
+}
+
+impl ResultsScreen {
+    /// `campaign` holds what the battle changed in the campaign, if it was
+    /// fought in one.
+    pub fn new(battle: &Battle, record: &GameRecord, campaign: Option<&[String]>, screen_w: u32, font: PixelFont) -> Self {
+        let mut lines = Vec::new();
+        match battle.outcome() {
+            Some((winner, objective)) => {
+                let title = if winner == Side::Player { "victory" } else { "defeat" };
+                lines.push(format!("{} - {} won ({} to {})", title, winner.name(), objective.side.name(), objective));
+            }
+            None => lines.push("nobody won".to_owned()),
+        }
+        lines.push(format!("turns: {}", record.turns));
+        lines.push(String::new());
+        lines.push("side    class        dealt  taken  turns".to_owned());
+        for unit in unit_records(record) {
+            lines.push(format!(
+                "{:<7} {:<12} {:>5}  {:>5}  {:>5}",
+                unit.side.name(),
+                unit.class.name(),
+                unit.damage_dealt,
+                unit.damage_taken,
+                unit.survived_turns
+            ));
+        }
+        if let Some(messages) = campaign {
+            lines.push(String::new());
+            lines.extend(messages.iter().cloned());
+        }
+        lines.push(String::new());
+        lines.push("Enter continues".to_owned());
+        Self {
+            screen_w,
+            lines,
+            font,
+        }
+    }
+
+}
+
+impl Scene for ResultsScreen {
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let height = self.lines.len() as f32 * LINE_HEIGHT + 2.0 * PADDING;
+        let width = self.screen_w as f32 - 2.0 * ORIGIN.0;
+        graphics::set_color(ctx, Color::new(0.0, 0.0, 0.0, 0.85)).unwrap();
+        graphics::rectangle(ctx, DrawMode::Fill, Rect::new(ORIGIN.0, ORIGIN.1, width, height)).unwrap();
+        graphics::set_color(ctx, graphics::WHITE).unwrap();
+        let mut cursor = Point2::new(ORIGIN.0 + PADDING, ORIGIN.1 + PADDING);
+        for line in &self.lines {
+            self.font.draw(ctx, line, cursor, 1.0)?;
+            cursor.y += LINE_HEIGHT;
+        }
+        Ok(())
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, keycode: Keycode, _keymod: Mod) -> Transition {
+        match keycode {
+            Keycode::Return | Keycode::Escape => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+
+    fn mouse_button_down(&mut self, _ctx: &mut Context, _button: MouseButton, _x: i32, _y: i32) -> Transition {
+        Transition::Pop
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,77 @@
+use event_bus::GameEvent;
+use ggez::event::*;
+use ggez::graphics;
+use ggez::graphics::{Color, Point2};
+use ggez::{Context, GameResult};
+use main_state::menu::MainMenu;
+use main_state::pixel_font::PixelFont;
+use scene::{Scene, Transition};
+use settings::Settings;
+
+pub const GAME_TITLE: &str = "The Long Night";
+const LINE_HEIGHT: f32 = 7.0;
+
+/// Shown once at startup, until a key or button is pressed.
+pub struct TitleScene {
+    screen_w: u32,
+    screen_h: u32,
+    settings: Settings,
+    font: PixelFont,
+    /// Shown along the bottom, as startup is when most things go wrong.
+    warnings: Vec<String>,
+}
+
+impl TitleScene {
+    pub fn new(screen_w: u32, screen_h: u32, settings: Settings, font: PixelFont) -> Self {
+        Self {
+            screen_w,
+            screen_h,
+            settings,
+            font,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn centred(&self, ctx: &mut Context, text: &str, y: f32, scale: f32) -> GameResult<()> {
+        let x = ((self.screen_w as f32 - self.font.width(text, scale)) / 2.0).floor();
+        self.font.draw(ctx, text, Point2::new(x, y), scale)
+    }
+
+    fn main_menu(&self, ctx: &mut Context) -> Transition {
+        let menu = MainMenu::new(ctx, self.screen_w, self.screen_h, self.settings.clone(), self.font.clone());
+        Transition::Replace(Box::new(menu))
+    }
+}
+
+impl Scene for TitleScene {
+    fn music(&self) -> Option<&'static str> {
+        Some("title")
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let middle = self.screen_h as f32 / 2.0;
+        graphics::set_color(ctx, graphics::WHITE)?;
+        self.centred(ctx, GAME_TITLE, middle - 16.0, 2.0)?;
+        self.centred(ctx, "press any key", middle + 8.0, 1.0)?;
+        graphics::set_color(ctx, Color::new(0.8, 0.6, 0.1, 1.0))?;
+        let top = self.screen_h as f32 - (self.warnings.len() + 1) as f32 * LINE_HEIGHT;
+        for (index, warning) in self.warnings.iter().enumerate() {
+            self.centred(ctx, warning, top + index as f32 * LINE_HEIGHT, 1.0)?;
+        }
+        Ok(())
+    }
+
+    fn notify(&mut self, event: &GameEvent) {
+        if let GameEvent::Warning(ref warning) = *event {
+            self.warnings.push(warning.clone());
+        }
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, _keycode: Keycode, _keymod: Mod) -> Transition {
+        self.main_menu(ctx)
+    }
+
+    fn mouse_button_down(&mut self, ctx: &mut Context, _button: MouseButton, _x: i32, _y: i32) -> Transition {
+        self.main_menu(ctx)
+    }
+}
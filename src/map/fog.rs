@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+use battle::Battle;
+use game_object::*;
+use map::line_of_sight::has_line_of_sight;
+
+/// Tiles `side` can currently see: those within a unit's vision radius
+/// that are in its line of sight.
+pub fn visible_tiles(battle: &Battle, side: Side) -> HashSet<Tile> {
+    let mut visible = HashSet::new();
+    let units = battle.units();
+    for unit_id in units.ids_of(side) {
+        let unit = match units.get_unit(unit_id) {
+            Some(unit) => unit,
+            None => continue,
+        };
+        let (origin, radius) = (unit.get_tile(), unit.get_vision() as i32);
+        for x in -radius..radius + 1 {
+            for y in -radius..radius + 1 {
+                let tile = origin + Tile::new(x as i16, y as i16);
+                if visible.contains(&tile) || !battle.in_bounds(tile) || origin.distance(tile) > radius as u32 {
+                    continue;
+                }
+                if has_line_of_sight(battle, origin, tile, Some(unit_id)) {
+                    visible.insert(tile);
+                }
+            }
+        }
+    }
+    visible
+}
+
+/// What one side can see now, what it has seen before and where it last
+/// saw each enemy unit.
+pub struct FogOfWar {
+    side: Side,
+    visible: HashSet<Tile>,
+    explored: HashSet<Tile>,
+    last_seen: HashMap<UnitId, Tile>,
+}
+
+impl FogOfWar {
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            visible: HashSet::new(),
+            explored: HashSet::new(),
+            last_seen: HashMap::new(),
+        }
+    }
+
+    /// Starts out knowing where every enemy is, as a side does from the
+    /// deployment it was shown before a battle.
+    pub fn with_known_positions(side: Side, battle: &Battle) -> Self {
+        let mut fog = FogOfWar::new(side);
+        for unit_id in battle.units().ids_of(side.opponent()) {
+            if let Some(unit) = battle.units().get_unit(unit_id) {
+                fog.last_seen.insert(unit_id, unit.get_tile());
+            }
+        }
+        fog.update(battle);
+        fog
+    }
+
+    pub fn update(&mut self, battle: &Battle) {
+        self.visible = battle.visible_tiles(self.side);
+        self.explored.extend(self.visible.iter().cloned());
+        let units = battle.units();
+        let visible = &self.visible;
+        // forget sightings that can be seen to be out of date
+        self.last_seen.retain(|unit_id, tile| {
+            let still_there = units.unit_at(*tile) == Some(*unit_id);
+            units.get_unit(*unit_id).is_some() && (still_there || !visible.contains(tile))
+        });
+        for unit_id in units.ids_of(self.side.opponent()) {
+            if let Some(unit) = units.get_unit(unit_id) {
+                if visible.contains(&unit.get_tile()) {
+                    self.last_seen.insert(unit_id, unit.get_tile());
+                }
+            }
+        }
+    }
+
+    /// Forgets everything explored, for when a new battle starts.
+    pub fn reset(&mut self) {
+        self.visible.clear();
+        self.explored.clear();
+        self.last_seen.clear();
+    }
+
+    /// The battle as this side knows it: enemies out of sight are placed
+    /// where they were last seen, or left out if that's unknown.
+    pub fn view(&self, battle: &Battle) -> Battle {
+        let mut view = battle.clone();
+        for unit_id in battle.units().ids_of(self.side.opponent()) {
+            let tile = match battle.units().get_unit(unit_id) {
+                Some(unit) => unit.get_tile(),
+                None => continue,
+            };
+            if self.visible.contains(&tile) {
+                continue;
+            }
+            let remembered = self.last_seen
+                .get(&unit_id)
+                .cloned()
+                .filter(|tile| view.units().unit_at(*tile).map(|id| id == unit_id).unwrap_or(true));
+            match remembered {
+                Some(tile) => view.units_mut().get_unit_mut(unit_id).unwrap().set_tile(tile),
+                None => {
+                    view.units_mut().remove_unit(unit_id);
+                }
+            }
+        }
+        view
+    }
+
+    pub fn is_visible(&self, tile: Tile) -> bool {
+        self.visible.contains(&tile)
+    }
+
+    pub fn is_explored(&self, tile: Tile) -> bool {
+        self.explored.contains(&tile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::Terrain;
+
+    /// A long field with a player warrior at 0,1, seeing four tiles, an
+    /// enemy close by at 3,1 and another far off at 11,0.
+    fn field() -> (Battle, UnitId, UnitId, UnitId) {
+        let mut battle = Battle::with_size(0, 12, 3);
+        battle.set_fog_of_war(true);
+        let player = battle.units_mut().make_unit(Unit::warrior(Tile::new(0, 1), Side::Player));
+        let near = battle.units_mut().make_unit(Unit::warrior(Tile::new(3, 1), Side::Enemy));
+        let far = battle.units_mut().make_unit(Unit::warrior(Tile::new(11, 0), Side::Enemy));
+        (battle, player, near, far)
+    }
+
+    fn tile_of(battle: &Battle, unit_id: UnitId) -> Option<Tile> {
+        battle.units().get_unit(unit_id).map(|unit| unit.get_tile())
+    }
+
+    #[test]
+    fn sees_within_vision_and_line_of_sight() {
+        let (mut battle, _, _, _) = field();
+        let visible = visible_tiles(&battle, Side::Player);
+        assert!(visible.contains(&Tile::new(4, 1)));
+        assert!(!visible.contains(&Tile::new(5, 1)));
+        assert!(!visible.contains(&Tile::new(4, 0)));
+        battle.map_mut().set_terrain(Tile::new(2, 1), Terrain::Wall);
+        let visible = visible_tiles(&battle, Side::Player);
+        assert!(visible.contains(&Tile::new(1, 1)));
+        assert!(!visible.contains(&Tile::new(3, 1)));
+        assert!(!visible.contains(&Tile::new(4, 1)));
+    }
+
+    #[test]
+    fn remembers_what_was_explored() {
+        let (mut battle, player, _, _) = field();
+        let mut fog = FogOfWar::new(Side::Player);
+        fog.update(&battle);
+        assert!(fog.is_visible(Tile::new(0, 1)) && fog.is_explored(Tile::new(0, 1)));
+        assert!(!fog.is_explored(Tile::new(8, 1)));
+
+        battle.units_mut().get_unit_mut(player).unwrap().set_tile(Tile::new(8, 1));
+        fog.update(&battle);
+        assert!(!fog.is_visible(Tile::new(0, 1)));
+        assert!(fog.is_explored(Tile::new(0, 1)));
+        assert!(fog.is_visible(Tile::new(8, 1)) && fog.is_explored(Tile::new(8, 1)));
+
+        fog.reset();
+        assert!(!fog.is_explored(Tile::new(8, 1)));
+    }
+
+    #[test]
+    fn view_hides_what_the_side_does_not_know() {
+        let (mut battle, player, near, far) = field();
+        let mut fog = FogOfWar::new(Side::Player);
+        fog.update(&battle);
+        let view = fog.view(&battle);
+        assert_eq!(tile_of(&view, player), Some(Tile::new(0, 1)));
+        assert_eq!(tile_of(&view, near), Some(Tile::new(3, 1)));
+        assert_eq!(tile_of(&view, far), None);
+        assert_eq!(tile_of(&battle, far), Some(Tile::new(11, 0)));
+
+        // Out of sight, the near enemy stays where it was last seen.
+        battle.units_mut().get_unit_mut(player).unwrap().set_tile(Tile::new(8, 1));
+        battle.units_mut().get_unit_mut(near).unwrap().set_tile(Tile::new(2, 2));
+        fog.update(&battle);
+        let view = fog.view(&battle);
+        assert_eq!(tile_of(&view, near), Some(Tile::new(3, 1)));
+        assert_eq!(tile_of(&view, far), Some(Tile::new(11, 0)));
+
+        // Seeing the old spot empty forgets the sighting.
+        battle.units_mut().get_unit_mut(player).unwrap().set_tile(Tile::new(6, 1));
+        battle.units_mut().get_unit_mut(near).unwrap().set_tile(Tile::new(0, 0));
+        fog.update(&battle);
+        assert_eq!(tile_of(&fog.view(&battle), near), None);
+    }
+
+    #[test]
+    fn known_positions_start_out_remembered() {
+        let (battle, _, near, far) = field();
+        let fog = FogOfWar::with_known_positions(Side::Player, &battle);
+        let view = fog.view(&battle);
+        assert_eq!(tile_of(&view, near), Some(Tile::new(3, 1)));
+        assert_eq!(tile_of(&view, far), Some(Tile::new(11, 0)));
+    }
+}
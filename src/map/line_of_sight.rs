@@ -0,0 +1,114 @@
+use battle::Battle;
+use game_object::*;
+
+/// Tiles on the Bresenham line from `from` to `to`, both ends included.
+pub fn line(from: Tile, to: Tile) -> Vec<Tile> {
+    let (dx, dy) = ((to.x() - from.x()).abs(), -(to.y() - from.y()).abs());
+    let (step_x, step_y) = ((to.x() - from.x()).signum(), (to.y() - from.y()).signum());
+    let (mut x, mut y) = (from.x(), from.y());
+    let mut error = dx + dy;
+    let mut tiles = vec![from];
+    while (x, y) != (to.x(), to.y()) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+        tiles.push(Tile::new(x as i16, y as i16));
+    }
+    tiles
+}
+
+fn blocks(battle: &Battle, tile: Tile, viewer: Option<UnitId>) -> bool {
+    if battle.map().terrain(tile).blocks_sight() {
+        return true;
+    }
+    battle.units_block_sight() && battle.units().unit_at(tile).map(|id| Some(id) != viewer).unwrap_or(false)
+}
+
+fn clear(battle: &Battle, tiles: &[Tile], viewer: Option<UnitId>) -> bool {
+    tiles.len() < 2 || tiles[1..tiles.len() - 1].iter().all(|tile| !blocks(battle, *tile, viewer))
+}
+
+/// Whether `to` can be seen from `from`. Only the tiles in between can block,
+/// and the viewing unit never blocks its own view. A line is drawn each way
+/// and either being clear is enough, so sight is always mutual.
+pub fn has_line_of_sight(battle: &Battle, from: Tile, to: Tile, viewer: Option<UnitId>) -> bool {
+    clear(battle, &line(from, to), viewer) || clear(battle, &line(to, from), viewer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::Terrain;
+
+    #[test]
+    fn lines_include_both_ends() {
+        assert_eq!(line(Tile::new(0, 0), Tile::new(3, 0)), vec![
+            Tile::new(0, 0),
+            Tile::new(1, 0),
+            Tile::new(2, 0),
+            Tile::new(3, 0),
+        ]);
+        assert_eq!(line(Tile::new(2, 2), Tile::new(2, 2)), vec![Tile::new(2, 2)]);
+        let diagonal = line(Tile::new(0, 0), Tile::new(3, 3));
+        assert_eq!(diagonal.len(), 4);
+        assert_eq!(diagonal.last(), Some(&Tile::new(3, 3)));
+    }
+
+    #[test]
+    fn walls_block_sight() {
+        let mut battle = Battle::with_size(0, 5, 3);
+        let (from, to) = (Tile::new(0, 1), Tile::new(4, 1));
+        assert!(has_line_of_sight(&battle, from, to, None));
+        battle.map_mut().set_terrain(Tile::new(2, 1), Terrain::Wall);
+        assert!(!has_line_of_sight(&battle, from, to, None));
+        // Walls only block in between, not at either end.
+        assert!(has_line_of_sight(&battle, from, Tile::new(2, 1), None));
+    }
+
+    #[test]
+    fn units_block_sight_only_when_the_battle_says_so() {
+        let mut battle = Battle::with_size(0, 5, 3);
+        let viewer = battle.units_mut().make_unit(Unit::warrior(Tile::new(0, 1), Side::Player));
+        battle.units_mut().make_unit(Unit::warrior(Tile::new(2, 1), Side::Enemy));
+        let (from, to) = (Tile::new(0, 1), Tile::new(4, 1));
+        assert!(has_line_of_sight(&battle, from, to, Some(viewer)));
+        battle.set_units_block_sight(true);
+        assert!(!has_line_of_sight(&battle, from, to, Some(viewer)));
+        assert!(has_line_of_sight(&battle, from, Tile::new(1, 0), Some(viewer)));
+
+        // Nobody blocks their own view.
+        battle.units_mut().get_unit_mut(viewer).unwrap().set_tile(Tile::new(1, 1));
+        assert!(has_line_of_sight(&battle, Tile::new(1, 1), Tile::new(1, 2), Some(viewer)));
+        assert!(has_line_of_sight(&battle, Tile::new(0, 1), Tile::new(2, 1), Some(viewer)));
+        assert!(!has_line_of_sight(&battle, Tile::new(0, 1), Tile::new(2, 1), None));
+    }
+
+    #[test]
+    fn sight_is_mutual() {
+        let mut battle = Battle::with_size(0, 7, 7);
+        for &(x, y) in &[(2, 1), (3, 3), (4, 5), (1, 4), (5, 2)] {
+            battle.map_mut().set_terrain(Tile::new(x, y), Terrain::Wall);
+        }
+        battle.units_mut().make_unit(Unit::warrior(Tile::new(3, 1), Side::Player));
+        battle.units_mut().make_unit(Unit::warrior(Tile::new(2, 5), Side::Enemy));
+        battle.set_units_block_sight(true);
+        let tiles = battle.map().tiles();
+        for &from in &tiles {
+            for &to in &tiles {
+                assert_eq!(
+                    has_line_of_sight(&battle, from, to, None),
+                    has_line_of_sight(&battle, to, from, None),
+                    "{:?} and {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use assets::SpriteName;
+use game_object::*;
+
+pub mod fog;
+pub mod line_of_sight;
+pub mod pathfinding;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Terrain {
+    Floor,
+    Rubble,
+    Wall,
+}
+
+impl Terrain {
+    pub fn all() -> Vec<Terrain> {
+        vec![Terrain::Floor, Terrain::Rubble, Terrain::Wall]
+    }
+
+    pub fn from_name(name: &str) -> Option<Terrain> {
+        Terrain::all().into_iter().find(|terrain| terrain.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Terrain::Floor => "floor",
+            Terrain::Rubble => "rubble",
+            Terrain::Wall => "wall",
+        }
+    }
+
+    /// Movement points it costs to step onto the tile, or `None` if it can't be entered.
+    pub fn move_cost(&self) -> Option<u32> {
+        match *self {
+            Terrain::Floor => Some(1),
+            Terrain::Rubble => Some(2),
+            Terrain::Wall => None,
+        }
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        *self == Terrain::Wall
+    }
+
+    pub fn get_sprite(&self) -> SpriteName {
+        match *self {
+            Terrain::Floor => SpriteName::Floor,
+            Terrain::Rubble => SpriteName::Rubble,
+            Terrain::Wall => SpriteName::Wall,
+        }
+    }
+}
+
+/// The terrain of every tile a battle is fought on.
+#[derive(Clone, Debug)]
+pub struct Map {
+    width: i32,
+    height: i32,
+    terrain: Vec<Terrain>,
+}
+
+impl Map {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            terrain: vec![Terrain::Floor; (width.max(0) * height.max(0)) as usize],
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn in_bounds(&self, tile: Tile) -> bool {
+        tile.x() >= 0 && tile.y() >= 0 && tile.x() < self.width && tile.y() < self.height
+    }
+
+    fn index(&self, tile: Tile) -> Option<usize> {
+        if self.in_bounds(tile) {
+            Some((tile.y() * self.width + tile.x()) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Terrain at `tile`; everything outside the map counts as wall.
+    pub fn terrain(&self, tile: Tile) -> Terrain {
+        self.index(tile).map(|index| self.terrain[index]).unwrap_or(Terrain::Wall)
+    }
+
+    pub fn set_terrain(&mut self, tile: Tile, terrain: Terrain) {
+        if let Some(index) = self.index(tile) {
+            self.terrain[index] = terrain;
+        }
+    }
+
+    pub fn move_cost(&self, tile: Tile) -> Option<u32> {
+        self.terrain(tile).move_cost()
+    }
+
+    pub fn tiles(&self) -> Vec<Tile> {
+        let mut tiles = Vec::with_capacity(self.terrain.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                tiles.push(Tile::new(x as i16, y as i16));
+            }
+        }
+        tiles
+    }
+}
@@ -0,0 +1,285 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use battle::Battle;
+use game_object::*;
+
+/// A route for a unit, not including the tile it starts on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Path {
+    pub tiles: Vec<Tile>,
+    pub cost: u32,
+}
+
+impl Path {
+    pub fn destination(&self) -> Option<Tile> {
+        self.tiles.last().cloned()
+    }
+}
+
+#[derive(Eq, PartialEq)]
+struct Open {
+    estimate: u32,
+    cost: u32,
+    tile: Tile,
+}
+
+// BinaryHeap is a max-heap, so the cheapest estimate compares greatest.
+// Ties are broken on the tile so the chosen path never depends on hashing.
+impl Ord for Open {
+    fn cmp(&self, other: &Open) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then(other.cost.cmp(&self.cost))
+            .then((other.tile.x(), other.tile.y()).cmp(&(self.tile.x(), self.tile.y())))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Open) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds where one unit can walk this turn. Terrain sets the cost of each
+/// step, allies can be walked through but not stopped on, enemies block,
+/// and with zone of control a unit must stop on entering a tile next to an
+/// enemy.
+pub struct Pathfinder<'a> {
+    battle: &'a Battle,
+    unit_id: UnitId,
+    side: Side,
+    start: Tile,
+    budget: u32,
+    zone_of_control: bool,
+}
+
+impl<'a> Pathfinder<'a> {
+    pub fn new(battle: &'a Battle, unit_id: UnitId) -> Option<Self> {
+        let unit = battle.units().get_unit(unit_id)?;
+        Some(Self {
+            battle,
+            unit_id,
+            side: unit.get_side(),
+            start: unit.get_tile(),
+            budget: unit.get_movement() as u32,
+            zone_of_control: battle.zone_of_control(),
+        })
+    }
+
+    pub fn with_budget(mut self, budget: u32) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn with_zone_of_control(mut self, zone_of_control: bool) -> Self {
+        self.zone_of_control = zone_of_control;
+        self
+    }
+
+    fn side_at(&self, tile: Tile) -> Option<Side> {
+        let units = self.battle.units();
+        units
+            .unit_at(tile)
+            .filter(|id| *id != self.unit_id)
+            .and_then(|id| units.get_unit(id))
+            .map(|unit| unit.get_side())
+    }
+
+    fn step_cost(&self, tile: Tile) -> Option<u32> {
+        if self.side_at(tile) == Some(self.side.opponent()) {
+            return None;
+        }
+        self.battle.map().move_cost(tile)
+    }
+
+    fn can_stop_at(&self, tile: Tile) -> bool {
+        self.side_at(tile).is_none()
+    }
+
+    fn in_enemy_zone(&self, tile: Tile) -> bool {
+        self.zone_of_control
+            && tile
+                .neighbours()
+                .into_iter()
+                .any(|next| self.side_at(next) == Some(self.side.opponent()))
+    }
+
+    /// Tiles that can be stepped to from `tile`, with the total cost of getting there.
+    fn expand(&self, tile: Tile, cost: u32) -> Vec<(Tile, u32)> {
+        if tile != self.start && self.in_enemy_zone(tile) {
+            return Vec::new();
+        }
+        tile.neighbours()
+            .into_iter()
+            .filter_map(|next| self.step_cost(next).map(|step| (next, cost + step)))
+            .filter(|&(_, cost)| cost <= self.budget)
+            .collect()
+    }
+
+    /// Cheapest path to `goal` within the movement budget, using A* with a
+    /// manhattan distance heuristic.
+    pub fn find_path(&self, goal: Tile) -> Option<Path> {
+        if goal == self.start || !self.can_stop_at(goal) {
+            return None;
+        }
+        let mut open = BinaryHeap::new();
+        let mut costs: HashMap<Tile, u32> = HashMap::new();
+        let mut came_from: HashMap<Tile, Tile> = HashMap::new();
+        costs.insert(self.start, 0);
+        open.push(Open {
+            estimate: self.start.distance(goal),
+            cost: 0,
+            tile: self.start,
+        });
+        while let Some(Open { cost, tile, .. }) = open.pop() {
+            if tile == goal {
+                return Some(Path {
+                    tiles: walk_back(&came_from, self.start, goal),
+                    cost,
+                });
+            }
+            if costs.get(&tile).map(|best| cost > *best).unwrap_or(false) {
+                continue;
+            }
+            for (next, next_cost) in self.expand(tile, cost) {
+                if costs.get(&next).map(|best| next_cost >= *best).unwrap_or(false) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                came_from.insert(next, tile);
+                open.push(Open {
+                    estimate: next_cost + next.distance(goal),
+                    cost: next_cost,
+                    tile: next,
+                });
+            }
+        }
+        None
+    }
+
+    /// Every tile the unit could end its move on, with the cheapest cost of reaching it.
+    pub fn reachable(&self) -> HashMap<Tile, u32> {
+        let mut open = BinaryHeap::new();
+        let mut costs: HashMap<Tile, u32> = HashMap::new();
+        costs.insert(self.start, 0);
+        open.push(Open {
+            estimate: 0,
+            cost: 0,
+            tile: self.start,
+        });
+        while let Some(Open { cost, tile, .. }) = open.pop() {
+            if costs.get(&tile).map(|best| cost > *best).unwrap_or(false) {
+                continue;
+            }
+            for (next, next_cost) in self.expand(tile, cost) {
+                if costs.get(&next).map(|best| next_cost >= *best).unwrap_or(false) {
+                    continue;
+                }
+                costs.insert(next, next_cost);
+                open.push(Open {
+                    estimate: next_cost,
+                    cost: next_cost,
+                    tile: next,
+                });
+            }
+        }
+        costs.remove(&self.start);
+        costs.retain(|tile, _| self.can_stop_at(*tile));
+        costs
+    }
+}
+
+fn walk_back(came_from: &HashMap<Tile, Tile>, start: Tile, goal: Tile) -> Vec<Tile> {
+    let mut tiles = vec![goal];
+    let mut tile = goal;
+    while let Some(previous) = came_from.get(&tile) {
+        if *previous == start {
+            break;
+        }
+        tiles.push(*previous);
+        tile = *previous;
+    }
+    tiles.reverse();
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map::Terrain;
+
+    /// A battle `width` by `height` with a player warrior at `start`.
+    fn field(width: i32, height: i32, start: Tile) -> (Battle, UnitId) {
+        let mut battle = Battle::with_size(0, width, height);
+        let unit = battle.units_mut().make_unit(Unit::warrior(start, Side::Player));
+        (battle, unit)
+    }
+
+    #[test]
+    fn routes_around_costly_terrain() {
+        let (mut battle, unit) = field(5, 3, Tile::new(0, 1));
+        for x in 1..4 {
+            battle.map_mut().set_terrain(Tile::new(x, 1), Terrain::Rubble);
+        }
+        let pathfinder = Pathfinder::new(&battle, unit).unwrap().with_budget(10);
+        let path = pathfinder.find_path(Tile::new(4, 1)).unwrap();
+        assert_eq!(path.cost, 6);
+        assert_eq!(path.tiles.len(), 6);
+        assert!(path.tiles.iter().all(|tile| battle.map().terrain(*tile) == Terrain::Floor));
+        assert_eq!(pathfinder.reachable().get(&Tile::new(1, 1)), Some(&2));
+        let short = Pathfinder::new(&battle, unit).unwrap().with_budget(5);
+        assert_eq!(short.find_path(Tile::new(4, 1)), None);
+    }
+
+    #[test]
+    fn walls_block_the_way() {
+        let (mut battle, unit) = field(5, 3, Tile::new(0, 1));
+        for y in 0..3 {
+            battle.map_mut().set_terrain(Tile::new(2, y), Terrain::Wall);
+        }
+        let pathfinder = Pathfinder::new(&battle, unit).unwrap().with_budget(10);
+        assert_eq!(pathfinder.find_path(Tile::new(4, 1)), None);
+        assert_eq!(pathfinder.find_path(Tile::new(2, 1)), None);
+        assert!(pathfinder.reachable().keys().all(|tile| tile.x() < 2));
+        assert!(pathfinder.find_path(Tile::new(1, 2)).is_some());
+    }
+
+    #[test]
+    fn passes_allies_but_not_enemies() {
+        let (mut battle, unit) = field(5, 1, Tile::new(0, 0));
+        let ally = battle.units_mut().make_unit(Unit::warrior(Tile::new(1, 0), Side::Player));
+        {
+            let pathfinder = Pathfinder::new(&battle, unit).unwrap().with_budget(10);
+            let path = pathfinder.find_path(Tile::new(2, 0)).unwrap();
+            assert_eq!(path.tiles, vec![Tile::new(1, 0), Tile::new(2, 0)]);
+            assert_eq!(pathfinder.find_path(Tile::new(1, 0)), None);
+            assert!(!pathfinder.reachable().contains_key(&Tile::new(1, 0)));
+        }
+        battle.units_mut().remove_unit(ally);
+        battle.units_mut().make_unit(Unit::warrior(Tile::new(1, 0), Side::Enemy));
+        let pathfinder = Pathfinder::new(&battle, unit).unwrap().with_budget(10);
+        assert_eq!(pathfinder.find_path(Tile::new(2, 0)), None);
+        assert!(pathfinder.reachable().is_empty());
+    }
+
+    #[test]
+    fn stops_on_entering_an_enemy_zone() {
+        let (mut battle, unit) = field(5, 3, Tile::new(0, 1));
+        battle.units_mut().make_unit(Unit::warrior(Tile::new(3, 1), Side::Enemy));
+        let free = Pathfinder::new(&battle, unit).unwrap().with_budget(10).with_zone_of_control(false);
+        assert!(free.reachable().contains_key(&Tile::new(4, 0)));
+
+        let zoned = Pathfinder::new(&battle, unit).unwrap().with_budget(10).with_zone_of_control(true);
+        let reachable = zoned.reachable();
+        assert!(reachable.contains_key(&Tile::new(2, 1)));
+        assert!(reachable.contains_key(&Tile::new(3, 0)));
+        assert!(!reachable.contains_key(&Tile::new(4, 0)));
+        assert_eq!(zoned.find_path(Tile::new(4, 1)), None);
+
+        // A unit already in the zone can still walk out of it.
+        battle.units_mut().get_unit_mut(unit).unwrap().set_tile(Tile::new(2, 1));
+        let leaving = Pathfinder::new(&battle, unit).unwrap().with_budget(10).with_zone_of_control(true);
+        assert!(leaving.find_path(Tile::new(0, 1)).is_some());
+    }
+}
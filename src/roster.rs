@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+use battle::{Battle, BattleEvent};
+use deployment::DeploymentPlan;
+use game_object::*;
+use scenario::Scenario;
+
+/// Experience needed to go from level `n` to `n + 1` is `n` times this.
+pub const XP_PER_LEVEL: u32 = 10;
+pub const KILL_XP: u32 = 5;
+pub const SURVIVAL_XP: u32 = 3;
+/// Veterans reaching this level are promoted into their advanced class.
+pub const PROMOTION_LEVEL: u16 = 3;
+
+const NAMES: &[&str] = &[
+    "Mortis", "Ossa", "Vesper", "Marrow", "Grimsby", "Wisp", "Cinder", "Rattle", "Hollow", "Dregs", "Sable",
+    "Lurch",
+];
+
+#[derive(Debug)]
+pub enum RosterError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for RosterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RosterError::Io(ref e) => write!(f, "could not read roster: {}", e),
+            RosterError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for RosterError {
+    fn description(&self) -> &str {
+        "invalid roster"
+    }
+}
+
+impl From<io::Error> for RosterError {
+    fn from(e: io::Error) -> Self {
+        RosterError::Io(e)
+    }
+}
+
+/// A unit of the player's army that carries over from battle to battle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Veteran {
+    pub name: String,
+    pub class: UnitClass,
+    pub level: u16,
+    /// Experience towards the next level.
+    pub xp: u32,
+    pub kills: u32,
+}
+
+impl Veteran {
+    pub fn new(name: &str, class: UnitClass) -> Self {
+        Self {
+            name: name.to_owned(),
+            class,
+            level: 1,
+            xp: 0,
+            kills: 0,
+        }
+    }
+
+    pub fn xp_to_next_level(&self) -> u32 {
+        self.level as u32 * XP_PER_LEVEL
+    }
+
+    /// Deployment points: the class's cost and one more per level gained.
+    pub fn cost(&self) -> u32 {
+        self.class.cost() + self.level.saturating_sub(1) as u32
+    }
+
+    pub fn spawn(&self, tile: Tile) -> Unit {
+        self.class.spawn(tile, Side::Player).with_level(self.level)
+    }
+
+    /// Adds experience, levelling up and promoting as thresholds are crossed.
+    fn gain_xp(&mut self, xp: u32) -> Vec<Progress> {
+        let mut progress = Vec::new();
+        self.xp += xp;
+        while self.xp >= self.xp_to_next_level() {
+            self.xp -= self.xp_to_next_level();
+            self.level += 1;
+            progress.push(Progress::LevelUp {
+                name: self.name.clone(),
+                level: self.level,
+            });
+            if self.level >= PROMOTION_LEVEL {
+                if let Some(class) = self.class.promotion() {
+                    self.class = class;
+                    progress.push(Progress::Promoted {
+                        name: self.name.clone(),
+                        class,
+                    });
+                }
+            }
+        }
+        progress
+    }
+}
+
+/// Something that happened to a veteran after a battle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Progress {
+    GainedXp { name: String, xp: u32 },
+    LevelUp { name: String, level: u16 },
+    Promoted { name: String, class: UnitClass },
+    Fell { name: String },
+    Recruited { name: String, class: UnitClass },
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Progress::GainedXp { ref name, xp } => write!(f, "{} gained {} xp", name, xp),
+            Progress::LevelUp { ref name, level } => write!(f, "{} reached level {}", name, level),
+            Progress::Promoted { ref name, class } => write!(f, "{} was promoted to {}", name, class.name()),
+            Progress::Fell { ref name } => write!(f, "{} fell in battle", name),
+            Progress::Recruited { ref name, class } => write!(f, "{} joined as a {}", name, class.name()),
+        }
+    }
+}
+
+/// Which veteran, by roster index, each deployed unit is.
+pub type Deployment = HashMap<UnitId, usize>;
+
+/// The player's army, kept between battles in a plain text file with a
+/// veteran per line:
+///
+/// ```text
+/// veteran Mortis warrior 2 7 1
+/// ```
+///
+/// giving the name, class, level, experience and kills.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Roster {
+    pub veterans: Vec<Veteran>,
+}
+
+impl Roster {
+    /// A fresh army made of the scenario's player units.
+    pub fn from_scenario(scenario: &Scenario) -> Self {
+        let mut roster = Roster::default();
+        for placement in scenario.units.iter().filter(|placement| placement.side == Side::Player) {
+            roster.recruit(placement.class);
+        }
+        roster
+    }
+
+    /// Adds a level one unit under the first name nobody has.
+    pub fn recruit(&mut self, class: UnitClass) {
+        let name = (0..)
+            .map(|count| match count / NAMES.len() {
+                0 => NAMES[count].to_owned(),
+                round => format!("{}{}", NAMES[count % NAMES.len()], round + 1),
+            })
+            .find(|name| self.veterans.iter().all(|veteran| veteran.name != *name))
+            .unwrap();
+        self.veterans.push(Veteran::new(&name[..], class));
+    }
+
+    /// Builds the scenario's battle with the player's units replaced by
+    /// the veterans of the plan.
+    pub fn deploy(&self, scenario: &Scenario, plan: &DeploymentPlan, seed: u64) -> (Battle, Deployment) {
+        let mut battle = scenario.to_battle(seed);
+        for unit_id in battle.units().ids_of(Side::Player) {
+            battle.units_mut().remove_unit(unit_id);
+        }
+        let mut placements = plan.placements().to_vec();
+        placements.sort_by_key(|&(index, _)| index);
+        let mut deployment = Deployment::new();
+        for (index, tile) in placements {
+            if let Some(veteran) = self.veterans.get(index) {
+                let unit_id = battle.units_mut().make_unit(veteran.spawn(tile));
+                deployment.insert(unit_id, index);
+            }
+        }
+        (battle, deployment)
+    }
+
+    /// Hands out experience for a finished battle: damage dealt, health
+    /// healed, kills and surviving all count. Veterans that died are
+    /// struck off the roster.
+    pub fn record_battle(&mut self, deployment: &Deployment, battle: &Battle, events: &[BattleEvent]) -> Vec<Progress> {
+        let mut xp = vec![0; self.veterans.len()];
+        for event in events {
+            match *event {
+                BattleEvent::UnitDamaged { attacker, report, .. } => {
+                    if let Some(&index) = deployment.get(&attacker) {
+                        xp[index] += report.dealt as u32;
+                        if report.killed {
+                            xp[index] += KILL_XP;
+                            self.veterans[index].kills += 1;
+                        }
+                    }
+                }
+                BattleEvent::UnitHealed { healer, amount, .. } => {
+                    if let Some(&index) = deployment.get(&healer) {
+                        xp[index] += amount as u32;
+                    }
+                }
+                _ => (),
+            }
+        }
+        let mut deployed: Vec<(UnitId, usize)> = deployment.iter().map(|(id, index)| (*id, *index)).collect();
+        deployed.sort_by_key(|&(_, index)| index);
+        let mut progress = Vec::new();
+        let mut fallen = Vec::new();
+        for (unit_id, index) in deployed {
+            let veteran = &mut self.veterans[index];
+            if battle.units().get_unit(unit_id).is_none() {
+                progress.push(Progress::Fell { name: veteran.name.clone() });
+                fallen.push(index);
+                continue;
+            }
+            let gained = xp[index] + SURVIVAL_XP;
+            progress.push(Progress::GainedXp { name: veteran.name.clone(), xp: gained });
+            progress.extend(veteran.gain_xp(gained));
+        }
+        fallen.sort();
+        for index in fallen.into_iter().rev() {
+            self.veterans.remove(index);
+        }
+        progress
+    }
+
+    pub fn parse(text: &str) -> Result<Roster, RosterError> {
+        let mut roster = Roster::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let veteran = parse_veteran(&words).map_err(|message| RosterError::Parse { line: index + 1, message })?;
+            roster.veterans.push(veteran);
+        }
+        Ok(roster)
+    }
+
+    pub fn load(path: &Path) -> Result<Roster, RosterError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Roster::parse(&text[..])
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RosterError> {
+        let mut file = File::create(path)?;
+        file.write_all(self.to_string().as_bytes())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Roster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for veteran in &self.veterans {
+            writeln!(
+                f,
+                "veteran {} {} {} {} {}",
+                veteran.name,
+                veteran.class.name(),
+                veteran.level,
+                veteran.xp,
+                veteran.kills
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_veteran(words: &[&str]) -> Result<Veteran, String> {
+    if words[0] != "veteran" {
+        return Err(format!("unknown directive '{}'", words[0]));
+    }
+    let name = words.get(1).ok_or_else(|| "expected a name".to_owned())?;
+    let class = words.get(2)
+        .and_then(|word| UnitClass::from_name(word))
+        .ok_or_else(|| "expected a unit class".to_owned())?;
+    Ok(Veteran {
+        name: name.to_string(),
+        class,
+        level: parse_number(words.get(3))?,
+        xp: parse_number(words.get(4))?,
+        kills: parse_number(words.get(5))?,
+    })
+}
+
+fn parse_number<T: ::std::str::FromStr>(word: Option<&&str>) -> Result<T, String> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| "expected a number".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_fallen_leave_the_saved_roster() {
+        let mut roster = Roster::default();
+        roster.recruit(UnitClass::Warrior);
+        roster.recruit(UnitClass::Archer);
+        roster.recruit(UnitClass::Monk);
+        let mut battle = Battle::new(7);
+        let mut deployment = Deployment::new();
+        for (index, veteran) in roster.veterans.iter().enumerate() {
+            let unit_id = battle.units_mut().make_unit(veteran.spawn(Tile::new(index as i16, 0)));
+            deployment.insert(unit_id, index);
+        }
+        let fallen = *deployment.iter().find(|&(_, &index)| index == 1).unwrap().0;
+        battle.units_mut().remove_unit(fallen);
+
+        let progress = roster.record_battle(&deployment, &battle, &[]);
+        assert!(progress.contains(&Progress::Fell { name: "Ossa".to_owned() }));
+        let names: Vec<&str> = roster.veterans.iter().map(|veteran| &veteran.name[..]).collect();
+        assert_eq!(names, vec!["Mortis", "Vesper"]);
+        assert!(roster.veterans.iter().all(|veteran| veteran.xp == SURVIVAL_XP));
+
+        let saved = roster.to_string();
+        assert!(!saved.contains("Ossa"));
+        assert_eq!(Roster::parse(&saved).unwrap(), roster);
+    }
+}
@@ -0,0 +1,304 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use battle::{Battle, Condition, Objective};
+use console::{CommandRegistry, CommandResult};
+use game_object::*;
+use map::Terrain;
+use utils::{find_resource, resource_dirs};
+
+const SCENARIO_DIR: &str = "scenarios";
+const SCENARIO_EXTENSION: &str = "txt";
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    NotFound(String),
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScenarioError::NotFound(ref name) => write!(f, "scenario '{}' not found", name),
+            ScenarioError::Io(ref e) => write!(f, "could not read scenario: {}", e),
+            ScenarioError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for ScenarioError {
+    fn description(&self) -> &str {
+        "invalid scenario"
+    }
+}
+
+impl From<io::Error> for ScenarioError {
+    fn from(e: io::Error) -> Self {
+        ScenarioError::Io(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UnitPlacement {
+    pub class: UnitClass,
+    pub side: Side,
+    pub tile: Tile,
+    pub squad: Option<String>,
+}
+
+/// A battle setup read from a plain text file, one directive per line:
+///
+/// ```text
+/// name Skirmish
+/// size 10 10
+/// rule zone-of-control
+/// terrain wall 4 0 1 3
+/// terrain rubble 5 5
+/// unit player warrior 3 3
+/// unit enemy archer 6 3 flankers
+/// deploy 0 2 2 6
+/// budget 12
+/// objective player kill-king
+/// ai enemy veteran
+/// squad flankers recruit
+/// ```
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    /// Terrain laid over the default floor, as rectangles of `(terrain, corner, width, height)`.
+    pub terrain: Vec<(Terrain, Tile, i32, i32)>,
+    pub zone_of_control: bool,
+    pub units_block_sight: bool,
+    pub fog_of_war: bool,
+    pub units: Vec<UnitPlacement>,
+    /// Rectangles the player may deploy into, as `(corner, width, height)`.
+    pub deployment: Vec<(Tile, i32, i32)>,
+    /// Most points of units the player may deploy.
+    pub budget: Option<u32>,
+    /// What each side is fighting for besides wiping out the other.
+    pub objectives: Vec<Objective>,
+    /// AI profile names playing each side.
+    pub profiles: Vec<(Side, String)>,
+    /// Squad names and the AI profile each is played by.
+    pub squads: Vec<(String, String)>,
+}
+
+impl Scenario {
+    pub fn parse(name: &str, text: &str) -> Result<Scenario, ScenarioError> {
+        let mut scenario = Scenario {
+            name: name.to_owned(),
+            width: 10,
+            height: 10,
+            terrain: Vec::new(),
+            zone_of_control: false,
+            units_block_sight: false,
+            fog_of_war: false,
+            units: Vec::new(),
+            deployment: Vec::new(),
+            budget: None,
+            objectives: Vec::new(),
+            profiles: Vec::new(),
+            squads: Vec::new(),
+        };
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            scenario
+                .parse_directive(&words)
+                .map_err(|message| ScenarioError::Parse { line: index + 1, message })?;
+        }
+        Ok(scenario)
+    }
+
+    fn parse_directive(&mut self, words: &[&str]) -> Result<(), String> {
+        match words[0] {
+            "name" => self.name = words[1..].join(" "),
+            "size" => {
+                self.width = parse_number(words.get(1))?;
+                self.height = parse_number(words.get(2))?;
+            }
+            "rule" => match words.get(1) {
+                Some(&"zone-of-control") => self.zone_of_control = true,
+                Some(&"units-block-sight") => self.units_block_sight = true,
+                Some(&"fog-of-war") => self.fog_of_war = true,
+                _ => return Err("expected a rule".to_owned()),
+            },
+            "terrain" => {
+                let terrain = words.get(1)
+                    .and_then(|word| Terrain::from_name(word))
+                    .ok_or_else(|| "expected a terrain".to_owned())?;
+                let corner = Tile::new(parse_number(words.get(2))?, parse_number(words.get(3))?);
+                let width = if words.len() > 4 { parse_number(words.get(4))? } else { 1 };
+                let height = if words.len() > 5 { parse_number(words.get(5))? } else { 1 };
+                self.terrain.push((terrain, corner, width, height));
+            }
+            "unit" => {
+                let side = words.get(1)
+                    .and_then(|word| Side::from_name(word))
+                    .ok_or_else(|| "expected a side".to_owned())?;
+                let class = words.get(2)
+                    .and_then(|word| UnitClass::from_name(word))
+                    .ok_or_else(|| "expected a unit class".to_owned())?;
+                let tile = Tile::new(parse_number(words.get(3))?, parse_number(words.get(4))?);
+                let squad = words.get(5).map(|word| word.to_string());
+                self.units.push(UnitPlacement { class, side, tile, squad });
+            }
+            "deploy" => {
+                let corner = Tile::new(parse_number(words.get(1))?, parse_number(words.get(2))?);
+                let width = if words.len() > 3 { parse_number(words.get(3))? } else { 1 };
+                let height = if words.len() > 4 { parse_number(words.get(4))? } else { 1 };
+                self.deployment.push((corner, width, height));
+            }
+            "budget" => self.budget = Some(parse_number(words.get(1))?),
+            "objective" => {
+                let side = words.get(1)
+                    .and_then(|word| Side::from_name(word))
+                    .ok_or_else(|| "expected a side".to_owned())?;
+                let condition = Condition::parse(&words[2..])?;
+                self.objectives.push(Objective { side, condition });
+            }
+            "ai" => {
+                let side = words.get(1)
+                    .and_then(|word| Side::from_name(word))
+                    .ok_or_else(|| "expected a side".to_owned())?;
+                let profile = words.get(2).ok_or_else(|| "expected an AI profile".to_owned())?;
+                self.profiles.retain(|&(profile_side, _)| profile_side != side);
+                self.profiles.push((side, profile.to_string()));
+            }
+            "squad" => match (words.get(1), words.get(2)) {
+                (Some(squad), Some(profile)) => self.squads.push((squad.to_string(), profile.to_string())),
+                _ => return Err("expected a squad name and an AI profile".to_owned()),
+            },
+            directive => return Err(format!("unknown directive '{}'", directive)),
+        }
+        Ok(())
+    }
+
+    /// Loads a scenario by file path, or by name from the resources directory.
+    pub fn load(name: &str) -> Result<Scenario, ScenarioError> {
+        let path = find(name).ok_or_else(|| ScenarioError::NotFound(name.to_owned()))?;
+        let mut text = String::new();
+        File::open(&path)?.read_to_string(&mut text)?;
+        let stem = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(name)
+            .to_owned();
+        Scenario::parse(&stem[..], &text[..])
+    }
+
+    pub fn profile_for(&self, side: Side) -> Option<&str> {
+        self.profiles
+            .iter()
+            .find(|&&(profile_side, _)| profile_side == side)
+            .map(|&(_, ref profile)| &profile[..])
+    }
+
+    /// Tiles the player may deploy onto: the declared zone, or where the
+    /// scenario places the player's units if it declares none.
+    pub fn deployment_zone(&self) -> Vec<Tile> {
+        let mut tiles: Vec<Tile> = if self.deployment.is_empty() {
+            self.units
+                .iter()
+                .filter(|placement| placement.side == Side::Player)
+                .map(|placement| placement.tile)
+                .collect()
+        } else {
+            let mut tiles = Vec::new();
+            for &(corner, width, height) in &self.deployment {
+                for x in 0..width {
+                    for y in 0..height {
+                        tiles.push(corner + Tile::new(x as i16, y as i16));
+                    }
+                }
+            }
+            tiles
+        };
+        tiles.sort_by_key(|tile| (tile.x(), tile.y()));
+        tiles.dedup();
+        tiles
+    }
+
+    /// Classes the player loses the battle without.
+    pub fn protected_classes(&self) -> Vec<UnitClass> {
+        self.objectives
+            .iter()
+            .filter(|objective| objective.side == Side::Player)
+            .filter_map(|objective| match objective.condition {
+                Condition::Protect(class) => Some(class),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn to_battle(&self, seed: u64) -> Battle {
+        let mut battle = Battle::with_size(seed, self.width, self.height);
+        battle.set_zone_of_control(self.zone_of_control);
+        battle.set_units_block_sight(self.units_block_sight);
+        battle.set_fog_of_war(self.fog_of_war);
+        battle.set_objectives(self.objectives.clone());
+        for &(terrain, corner, width, height) in &self.terrain {
+            for x in 0..width {
+                for y in 0..height {
+                    battle
+                        .map_mut()
+                        .set_terrain(corner + Tile::new(x as i16, y as i16), terrain);
+                }
+            }
+        }
+        for placement in &self.units {
+            battle
+                .units_mut()
+                .make_unit(placement.class.spawn(placement.tile, placement.side));
+        }
+        battle
+    }
+}
+
+fn parse_number<T: ::std::str::FromStr>(word: Option<&&str>) -> Result<T, String> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| "expected a number".to_owned())
+}
+
+pub fn find(name: &str) -> Option<PathBuf> {
+    let direct = PathBuf::from(name);
+    if direct.is_file() {
+        return Some(direct);
+    }
+    find_resource(&Path::new(SCENARIO_DIR).join(name).with_extension(SCENARIO_EXTENSION))
+}
+
+/// Names of every scenario in the resource directories, sorted.
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = resource_dirs()
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir.join(SCENARIO_DIR)).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some(SCENARIO_EXTENSION))
+        .filter_map(|path| path.file_stem().and_then(|stem| stem.to_str()).map(str::to_owned))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn register_commands(registry: &mut CommandRegistry) {
+    registry.register("load scenario", "load scenario <name>", load_scenario);
+}
+
+fn load_scenario(battle: &mut Battle, args: &[&str]) -> CommandResult {
+    let name = args.get(0).ok_or_else(|| "expected a scenario name".to_owned())?;
+    let scenario = Scenario::load(name).map_err(|e| e.to_string())?;
+    *battle = scenario.to_battle(battle.seed());
+    Ok(format!("loaded {}", scenario.name))
+}
@@ -0,0 +1,239 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use audio::Audio;
+use event_bus::{EventBus, GameEvent};
+use ggez::conf::NumSamples;
+use ggez::event::*;
+use ggez::graphics;
+use ggez::graphics::{Canvas, DrawParam, Point2, Rect};
+use ggez::timer;
+use ggez::{Context, GameResult};
+use settings::Settings;
+use viewport::{Viewport, CANVAS_SIZE};
+
+/// What the stack should do after a scene has handled something.
+pub enum Transition {
+    None,
+    /// Puts a scene on top, keeping the current one underneath.
+    Push(Box<dyn Scene>),
+    /// Goes back to the scene underneath.
+    Pop,
+    /// Swaps the current scene for another.
+    Replace(Box<dyn Scene>),
+    /// Throws away the whole stack and starts over with the scene.
+    Reset(Box<dyn Scene>),
+    Quit,
+}
+
+/// One screen of the game. Only the scene on top of the stack gets input
+/// and updates.
+pub trait Scene {
+    fn update(&mut self, _ctx: &mut Context) -> GameResult<Transition> {
+        Ok(Transition::None)
+    }
+
+    /// Draws the scene. Clearing and presenting is left to the stack.
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()>;
+
+    fn key_down(&mut self, _ctx: &mut Context, _keycode: Keycode, _keymod: Mod) -> Transition {
+        Transition::None
+    }
+
+    fn text_input(&mut self, _ctx: &mut Context, _text: &str) {}
+
+    fn mouse_button_down(&mut self, _ctx: &mut Context, _button: MouseButton, _x: i32, _y: i32) -> Transition {
+        Transition::None
+    }
+
+    /// Positions are on the canvas, like clicks.
+    fn mouse_motion(&mut self, _ctx: &mut Context, _x: i32, _y: i32) {}
+
+    /// `y` is positive when the wheel is turned away from the player.
+    fn mouse_wheel(&mut self, _ctx: &mut Context, _y: i32) {}
+
+    /// Told about every published event, whether on top or not.
+    fn notify(&mut self, _event: &GameEvent) {}
+
+    /// The music to play while this is the top scene that has any.
+    fn music(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Overlays are drawn over the scene underneath instead of hiding it.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Events the scene has published since it was last asked, for the
+    /// stack to dispatch. Asked after each update or input, like
+    /// `saved_settings`.
+    fn take_events(&mut self) -> Vec<GameEvent> {
+        Vec::new()
+    }
+
+    /// Settings the scene has just saved, for the stack to start using.
+    /// Asked after the scene has handled each update or input.
+    fn saved_settings(&mut self) -> Option<Settings> {
+        None
+    }
+}
+
+/// Runs the scene on top, drawing every scene to a canvas of
+/// `CANVAS_SIZE` that is then scaled up to the window.
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    canvas: Canvas,
+    viewport: Viewport,
+    events: EventBus,
+    audio: Rc<RefCell<Audio>>,
+}
+
+impl SceneStack {
+    pub fn new(ctx: &mut Context, settings: &Settings, first: Box<dyn Scene>) -> GameResult<Self> {
+        graphics::set_default_filter(ctx, graphics::FilterMode::Nearest);
+        let canvas = Canvas::new(ctx, CANVAS_SIZE.0, CANVAS_SIZE.1, NumSamples::One)?;
+        let (window_w, window_h) = graphics::get_size(ctx);
+        let audio = Rc::new(RefCell::new(Audio::new(ctx, settings)));
+        let mut events = EventBus::new();
+        events.subscribe(audio.clone());
+        Ok(Self {
+            scenes: vec![first],
+            canvas,
+            viewport: Viewport::new(window_w, window_h, settings.ui_scale),
+            events,
+            audio,
+        })
+    }
+
+    fn apply(&mut self, ctx: &mut Context, transition: Transition) {
+        if let Some(scene) = self.scenes.last_mut() {
+            self.events.publish_all(scene.take_events());
+        }
+        if let Some(settings) = self.scenes.last_mut().and_then(|scene| scene.saved_settings()) {
+            self.viewport.set_max_scale(settings.ui_scale);
+            self.audio.borrow_mut().set_volumes(&settings);
+        }
+        match transition {
+            Transition::None => return,
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scene) => self.scenes = vec![scene],
+            Transition::Quit => self.scenes.clear(),
+        }
+        if self.scenes.is_empty() {
+            ctx.quit().unwrap();
+        }
+    }
+
+    /// Plays the top scene's music.
+    fn update_music(&mut self, ctx: &mut Context) {
+        let mut audio = self.audio.borrow_mut();
+        if let Some(music) = self.scenes.iter().rev().filter_map(|scene| scene.music()).next() {
+            audio.play_music(ctx, music);
+        }
+        audio.update(timer::duration_to_f64(timer::get_delta(ctx)) as f32);
+    }
+}
+
+impl EventHandler for SceneStack {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        const DESIRED_FPS: u32 = 60;
+        while timer::check_update_time(ctx, DESIRED_FPS) {
+            let _seconds = 1.0 / (DESIRED_FPS as f32);
+        }
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(ctx)?,
+            None => return Ok(()),
+        };
+        self.apply(ctx, transition);
+        for warning in self.audio.borrow_mut().take_warnings() {
+            self.events.publish(GameEvent::Warning(warning));
+        }
+        // Everything published this frame, including from input, goes out here.
+        for event in self.events.dispatch() {
+            for scene in &mut self.scenes {
+                scene.notify(&event);
+            }
+        }
+        self.update_music(ctx);
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        graphics::set_canvas(ctx, Some(&self.canvas));
+        graphics::set_screen_coordinates(ctx, Rect::new(0.0, 0.0, CANVAS_SIZE.0 as f32, CANVAS_SIZE.1 as f32))?;
+        graphics::clear(ctx);
+        let bottom = self.scenes
+            .iter()
+            .rposition(|scene| !scene.is_overlay())
+            .unwrap_or(0);
+        for scene in &mut self.scenes[bottom..] {
+            scene.draw(ctx)?;
+        }
+
+        graphics::set_canvas(ctx, None);
+        let (window_w, window_h) = self.viewport.window();
+        graphics::set_screen_coordinates(ctx, Rect::new(0.0, 0.0, window_w as f32, window_h as f32))?;
+        graphics::clear(ctx);
+        graphics::set_color(ctx, graphics::WHITE)?;
+        let scale = self.viewport.scale() as f32;
+        let (x, y) = self.viewport.offset();
+        let param = DrawParam {
+            dest: Point2::new(x as f32, y as f32),
+            scale: Point2::new(scale, scale),
+            ..Default::default()
+        };
+        graphics::draw_ex(ctx, &self.canvas, param)?;
+        graphics::present(ctx);
+        timer::yield_now();
+        Ok(())
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, keymod: Mod, _repeat: bool) {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.key_down(ctx, keycode, keymod),
+            None => return,
+        };
+        self.apply(ctx, transition);
+    }
+
+    fn text_input_event(&mut self, ctx: &mut Context, text: String) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.text_input(ctx, &text[..]);
+        }
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) {
+        let (x, y) = match self.viewport.to_canvas(x, y) {
+            Some(position) => position,
+            None => return,
+        };
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.mouse_button_down(ctx, button, x, y),
+            None => return,
+        };
+        self.apply(ctx, transition);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, _state: MouseState, x: i32, y: i32, _xrel: i32, _yrel: i32) {
+        if let (Some((x, y)), Some(scene)) = (self.viewport.to_canvas(x, y), self.scenes.last_mut()) {
+            scene.mouse_motion(ctx, x, y);
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: i32, y: i32) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.mouse_wheel(ctx, y);
+        }
+    }
+
+    fn resize_event(&mut self, _ctx: &mut Context, width: u32, height: u32) {
+        self.viewport.resize(width, height);
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use ggez::conf::{FullscreenType, WindowMode};
+use ggez::event::Keycode;
+
+const SETTINGS_FILE: &str = "settings.txt";
+pub const RESOLUTIONS: [(u32, u32); 4] = [(800, 450), (1280, 720), (1600, 900), (1920, 1080)];
+pub const ANIMATION_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+pub const MAX_UI_SCALE: u32 = 12;
+/// Window sizes outside these are taken to be a mistake in the file.
+const MIN_RESOLUTION: (u32, u32) = (640, 360);
+const MAX_RESOLUTION: (u32, u32) = (7680, 4320);
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SettingsError::Io(ref e) => write!(f, "could not read settings: {}", e),
+            SettingsError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for SettingsError {
+    fn description(&self) -> &str {
+        "invalid settings"
+    }
+}
+
+impl From<io::Error> for SettingsError {
+    fn from(e: io::Error) -> Self {
+        SettingsError::Io(e)
+    }
+}
+
+/// Colours used for sides and highlights.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Palette {
+    Standard,
+    /// Keeps to colours that stay apart with red-green colour blindness.
+    ColourBlind,
+}
+
+impl Palette {
+    pub fn all() -> Vec<Palette> {
+        vec![Palette::Standard, Palette::ColourBlind]
+    }
+
+    pub fn from_name(name: &str) -> Option<Palette> {
+        Palette::all().into_iter().find(|palette| palette.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Palette::Standard => "standard",
+            Palette::ColourBlind => "colour-blind",
+        }
+    }
+}
+
+/// Something the player can do with a single key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Control {
+    EndTurn,
+    HeatMap,
+    CampaignMap,
+    Pause,
+    Console,
+    CombatLog,
+}
+
+impl Control {
+    pub fn all() -> Vec<Control> {
+        vec![
+            Control::EndTurn,
+            Control::HeatMap,
+            Control::CampaignMap,
+            Control::Pause,
+            Control::Console,
+            Control::CombatLog,
+        ]
+    }
+
+    pub fn from_name(name: &str) -> Option<Control> {
+        Control::all().into_iter().find(|control| control.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Control::EndTurn => "end-turn",
+            Control::HeatMap => "heat-map",
+            Control::CampaignMap => "campaign-map",
+            Control::Pause => "pause",
+            Control::Console => "console",
+            Control::CombatLog => "combat-log",
+        }
+    }
+
+    fn default_key(&self) -> Keycode {
+        match *self {
+            Control::EndTurn => Keycode::Space,
+            Control::HeatMap => Keycode::H,
+            Control::CampaignMap => Keycode::M,
+            Control::Pause => Keycode::Escape,
+            Control::Console => Keycode::Backquote,
+            Control::CombatLog => Keycode::L,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    keys: HashMap<Control, Keycode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = Control::all()
+            .into_iter()
+            .map(|control| (control, control.default_key()))
+            .collect();
+        KeyBindings { keys }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, control: Control) -> Keycode {
+        self.keys[&control]
+    }
+
+    pub fn control(&self, keycode: Keycode) -> Option<Control> {
+        Control::all().into_iter().find(|control| self.keys[control] == keycode)
+    }
+
+    /// Binds the key, swapping keys with whatever control had it before.
+    pub fn bind(&mut self, control: Control, keycode: Keycode) {
+        if let Some(previous) = self.control(keycode) {
+            let old = self.key(control);
+            self.keys.insert(previous, old);
+        }
+        self.keys.insert(control, keycode);
+    }
+}
+
+/// Everything the player can change from the options screen, kept in the
+/// user config directory:
+///
+/// ```text
+/// resolution 1280 720
+/// fullscreen off
+/// ui-scale 8
+/// vsync on
+/// animation-speed 2
+/// volume 80
+/// music-volume 50
+/// effects-volume 100
+/// palette colour-blind
+/// key end-turn Return
+/// ```
+///
+/// The window settings only take effect the next time the game starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub resolution: (u32, u32),
+    pub fullscreen: bool,
+    /// The most screen pixels each art pixel is drawn as. Smaller windows
+    /// get the largest whole number that fits.
+    pub ui_scale: u32,
+    pub vsync: bool,
+    /// How much faster than normal the enemy's turns play out.
+    pub animation_speed: f32,
+    /// Master volume in percent, which the other volumes are a share of.
+    pub volume: u32,
+    pub music_volume: u32,
+    pub effects_volume: u32,
+    pub palette: Palette,
+    pub keys: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            resolution: RESOLUTIONS[0],
+            fullscreen: false,
+            ui_scale: 8,
+            vsync: true,
+            animation_speed: 1.0,
+            volume: 100,
+            music_volume: 70,
+            effects_volume: 100,
+            palette: Palette::Standard,
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join(SETTINGS_FILE)
+    }
+
+    pub fn parse(text: &str) -> Result<Settings, SettingsError> {
+        let mut settings = Settings::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            settings
+                .parse_directive(&words)
+                .map_err(|message| SettingsError::Parse { line: index + 1, message })?;
+        }
+        Ok(settings)
+    }
+
+    fn parse_directive(&mut self, words: &[&str]) -> Result<(), String> {
+        match words[0] {
+            "resolution" => {
+                let (width, height) = (parse_number(words.get(1))?, parse_number(words.get(2))?);
+                if width < MIN_RESOLUTION.0 || height < MIN_RESOLUTION.1 {
+                    return Err(format!("the resolution has to be at least {}x{}", MIN_RESOLUTION.0, MIN_RESOLUTION.1));
+                }
+                if width > MAX_RESOLUTION.0 || height > MAX_RESOLUTION.1 {
+                    return Err(format!("the resolution can be at most {}x{}", MAX_RESOLUTION.0, MAX_RESOLUTION.1));
+                }
+                self.resolution = (width, height);
+            }
+            "fullscreen" => self.fullscreen = parse_switch(words.get(1))?,
+            "ui-scale" => self.ui_scale = parse_number::<u32>(words.get(1))?.min(MAX_UI_SCALE).max(1),
+            "vsync" => self.vsync = parse_switch(words.get(1))?,
+            "animation-speed" => {
+                let speed: f32 = parse_number(words.get(1))?;
+                if speed <= 0.0 {
+                    return Err("the animation speed has to be above 0".to_owned());
+                }
+                self.animation_speed = speed;
+            }
+            "volume" => self.volume = parse_number::<u32>(words.get(1))?.min(100),
+            "music-volume" => self.music_volume = parse_number::<u32>(words.get(1))?.min(100),
+            "effects-volume" => self.effects_volume = parse_number::<u32>(words.get(1))?.min(100),
+            "palette" => {
+                self.palette = words.get(1)
+                    .and_then(|word| Palette::from_name(word))
+                    .ok_or_else(|| "expected a palette".to_owned())?
+            }
+            "key" => {
+                let control = words.get(1)
+                    .and_then(|word| Control::from_name(word))
+                    .ok_or_else(|| "expected a control".to_owned())?;
+                let name = words[2..].join(" ");
+                let keycode = Keycode::from_name(&name[..]).ok_or_else(|| format!("unknown key '{}'", name))?;
+                self.keys.bind(control, keycode);
+            }
+            directive => return Err(format!("unknown directive '{}'", directive)),
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Settings, SettingsError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Settings::parse(&text[..])
+    }
+
+    /// The saved settings, or the defaults if nothing has been saved yet.
+    pub fn load_or_default(config_dir: &Path) -> Result<Settings, SettingsError> {
+        let path = Settings::path(config_dir);
+        if !path.is_file() {
+            return Ok(Settings::default());
+        }
+        Settings::load(&path)
+    }
+
+    pub fn save(&self, config_dir: &Path) -> Result<(), SettingsError> {
+        fs::create_dir_all(config_dir)?;
+        let mut file = File::create(Settings::path(config_dir))?;
+        file.write_all(self.to_string().as_bytes())?;
+        Ok(())
+    }
+
+    /// How loud music plays, from 0 to 1.
+    pub fn music_gain(&self) -> f32 {
+        (self.volume * self.music_volume) as f32 / 10_000.0
+    }
+
+    /// How loud sound effects play, from 0 to 1.
+    pub fn effects_gain(&self) -> f32 {
+        (self.volume * self.effects_volume) as f32 / 10_000.0
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        let fullscreen = if self.fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Off
+        };
+        WindowMode::default()
+            .dimensions(self.resolution.0, self.resolution.1)
+            .fullscreen_type(fullscreen)
+            .vsync(self.vsync)
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let switch = |on: bool| if on { "on" } else { "off" };
+        writeln!(f, "resolution {} {}", self.resolution.0, self.resolution.1)?;
+        writeln!(f, "fullscreen {}", switch(self.fullscreen))?;
+        writeln!(f, "ui-scale {}", self.ui_scale)?;
+        writeln!(f, "vsync {}", switch(self.vsync))?;
+        writeln!(f, "animation-speed {}", self.animation_speed)?;
+        writeln!(f, "volume {}", self.volume)?;
+        writeln!(f, "music-volume {}", self.music_volume)?;
+        writeln!(f, "effects-volume {}", self.effects_volume)?;
+        writeln!(f, "palette {}", self.palette.name())?;
+        for control in Control::all() {
+            writeln!(f, "key {} {}", control.name(), self.keys.key(control).name())?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_number<T: ::std::str::FromStr>(word: Option<&&str>) -> Result<T, String> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| "expected a number".to_owned())
+}
+
+fn parse_switch(word: Option<&&str>) -> Result<bool, String> {
+    match word {
+        Some(&"on") => Ok(true),
+        Some(&"off") => Ok(false),
+        _ => Err("expected on or off".to_owned()),
+    }
+}
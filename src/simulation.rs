@@ -0,0 +1,77 @@
+use ai::{play_turn, Ai};
+use battle::{Battle, BattleEvent};
+use event_bus::{EventBus, GameEvent};
+use game_object::*;
+
+pub struct LogEntry {
+    pub turn: u32,
+    pub side: Side,
+    pub event: BattleEvent,
+}
+
+pub struct RosterEntry {
+    pub id: UnitId,
+    pub class: UnitClass,
+    pub side: Side,
+}
+
+pub struct GameRecord {
+    pub seed: u64,
+    pub roster: Vec<RosterEntry>,
+    pub winner: Option<Side>,
+    pub turns: u32,
+    pub log: Vec<LogEntry>,
+}
+
+impl GameRecord {
+    /// An empty record of the battle as it stands now.
+    pub fn start(battle: &Battle) -> GameRecord {
+        let mut roster: Vec<RosterEntry> = battle
+            .units()
+            .iter_with_ids()
+            .map(|(id, unit)| RosterEntry {
+                id: *id,
+                class: unit.get_class(),
+                side: unit.get_side(),
+            })
+            .collect();
+        roster.sort_by_key(|entry| entry.id);
+        GameRecord {
+            seed: battle.seed(),
+            roster,
+            winner: None,
+            turns: 0,
+            log: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, turn: u32, side: Side, event: BattleEvent) {
+        self.log.push(LogEntry { turn, side, event });
+    }
+
+    pub fn finish(&mut self, battle: &Battle, max_turns: u32) {
+        self.winner = battle.winner();
+        self.turns = battle.turn_number().min(max_turns);
+    }
+}
+
+/// Plays a battle to completion without a window, publishing what happens
+/// on the bus after each turn. The game is a draw if nobody has won after
+/// `max_turns`.
+pub fn run_game(mut battle: Battle, player: &mut dyn Ai, enemy: &mut dyn Ai, max_turns: u32, bus: &mut EventBus) -> GameRecord {
+    let mut record = GameRecord::start(&battle);
+    while !battle.is_over() && battle.turn_number() <= max_turns {
+        match battle.current_turn() {
+            Side::Player => play_turn(player, &mut battle),
+            Side::Enemy => play_turn(enemy, &mut battle),
+        }
+        bus.publish_all(battle.take_events());
+        for event in bus.dispatch() {
+            if let GameEvent::Battle { side, turn, event } = event {
+                record.add(turn, side, event);
+            }
+        }
+    }
+    record.finish(&battle, max_turns);
+    record
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use battle::BattleEvent;
+use game_object::*;
+use simulation::GameRecord;
+
+/// How a single unit fared over one game.
+pub struct UnitRecord {
+    pub class: UnitClass,
+    pub side: Side,
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub survived_turns: u32,
+    pub won: bool,
+}
+
+/// Damage from status effects counts as dealt by whoever used the ability
+/// that applied the effect.
+pub fn unit_records(record: &GameRecord) -> Vec<UnitRecord> {
+    let mut records: HashMap<UnitId, UnitRecord> = record
+        .roster
+        .iter()
+        .map(|entry| {
+            let unit_record = UnitRecord {
+                class: entry.class,
+                side: entry.side,
+                damage_dealt: 0,
+                damage_taken: 0,
+                survived_turns: record.turns,
+                won: record.winner == Some(entry.side),
+            };
+            (entry.id, unit_record)
+        })
+        .collect();
+    // The unit whose ability the events being read came from.
+    let mut caster = None;
+    let mut applied_by: HashMap<(UnitId, EffectKind), UnitId> = HashMap::new();
+    for entry in &record.log {
+        match entry.event {
+            BattleEvent::AbilityUsed { unit, .. } => caster = Some(unit),
+            BattleEvent::EffectApplied { unit, effect } => if let Some(caster) = caster {
+                applied_by.insert((unit, effect.kind), caster);
+            },
+            BattleEvent::EffectTicked { unit, kind, change, .. } if change < 0 => {
+                let damage = -change as u32;
+                if let Some(unit_record) = applied_by.get(&(unit, kind)).and_then(|source| records.get_mut(source)) {
+                    unit_record.damage_dealt += damage;
+                }
+                if let Some(unit_record) = records.get_mut(&unit) {
+                    unit_record.damage_taken += damage;
+                }
+            }
+            BattleEvent::UnitDamaged { attacker, target, report } => {
+                if let Some(unit_record) = records.get_mut(&attacker) {
+                    unit_record.damage_dealt += report.dealt as u32;
+                }
+                if let Some(unit_record) = records.get_mut(&target) {
+                    unit_record.damage_taken += report.dealt as u32;
+                }
+            }
+            BattleEvent::UnitDied { unit } => {
+                if let Some(unit_record) = records.get_mut(&unit) {
+                    unit_record.survived_turns = entry.turn;
+                }
+            }
+            _ => (),
+        }
+        match entry.event {
+            BattleEvent::AbilityUsed { .. }
+            | BattleEvent::UnitDamaged { .. }
+            | BattleEvent::UnitHealed { .. }
+            | BattleEvent::EffectApplied { .. }
+            | BattleEvent::UnitDied { .. }
+            | BattleEvent::UnitRaised { .. } => (),
+            _ => caster = None,
+        }
+    }
+    let mut ids: Vec<UnitId> = records.keys().cloned().collect();
+    ids.sort();
+    ids.into_iter().filter_map(|id| records.remove(&id)).collect()
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ClassStats {
+    pub units: u32,
+    pub wins: u32,
+    pub damage_dealt: u64,
+    pub damage_taken: u64,
+    pub survived_turns: u64,
+}
+
+impl ClassStats {
+    pub fn add(&mut self, record: &UnitRecord) {
+        self.units += 1;
+        if record.won {
+            self.wins += 1;
+        }
+        self.damage_dealt += record.damage_dealt as u64;
+        self.damage_taken += record.damage_taken as u64;
+        self.survived_turns += record.survived_turns as u64;
+    }
+
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins as f64, self.units as f64)
+    }
+
+    /// 95% Wilson score interval for the win rate.
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        wilson_interval(self.wins, self.units, 1.96)
+    }
+
+    pub fn avg_damage_dealt(&self) -> f64 {
+        ratio(self.damage_dealt as f64, self.units as f64)
+    }
+
+    pub fn avg_damage_taken(&self) -> f64 {
+        ratio(self.damage_taken as f64, self.units as f64)
+    }
+
+    pub fn avg_survived_turns(&self) -> f64 {
+        ratio(self.survived_turns as f64, self.units as f64)
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+pub fn wilson_interval(successes: u32, trials: u32, z: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let denominator = 1.0 + z * z / n;
+    let centre = p + z * z / (2.0 * n);
+    let spread = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt();
+    (
+        ((centre - spread) / denominator).max(0.0),
+        ((centre + spread) / denominator).min(1.0),
+    )
+}
+
+/// Per-class totals over many games, plus the overall unit win rate the
+/// classes are compared against.
+#[derive(Default)]
+pub struct ClassTable {
+    classes: HashMap<UnitClass, ClassStats>,
+    overall: ClassStats,
+}
+
+impl ClassTable {
+    pub fn add_game(&mut self, record: &GameRecord) {
+        for unit_record in unit_records(record) {
+            self.classes
+                .entry(unit_record.class)
+                .or_insert_with(ClassStats::default)
+                .add(&unit_record);
+            self.overall.add(&unit_record);
+        }
+    }
+
+    pub fn get(&self, class: UnitClass) -> ClassStats {
+        self.classes.get(&class).cloned().unwrap_or_default()
+    }
+
+    /// How far the class's win rate sits above or below the average unit.
+    pub fn contribution(&self, class: UnitClass) -> f64 {
+        self.get(class).win_rate() - self.overall.win_rate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simulation::{LogEntry, RosterEntry};
+
+    fn log(turn: u32, side: Side, event: BattleEvent) -> LogEntry {
+        LogEntry { turn, side, event }
+    }
+
+    #[test]
+    fn wilson_interval_brackets_the_win_rate() {
+        assert_eq!(wilson_interval(0, 0, 1.96), (0.0, 1.0));
+        let (low, high) = wilson_interval(50, 100, 1.96);
+        assert!((low - 0.4038).abs() < 1e-3 && (high - 0.5962).abs() < 1e-3);
+        let (low, high) = wilson_interval(0, 10, 1.96);
+        assert_eq!(low, 0.0);
+        assert!(high > 0.0 && high < 0.35);
+        let (low, high) = wilson_interval(10, 10, 1.96);
+        assert!(low > 0.65 && low < 1.0);
+        assert_eq!(high, 1.0);
+    }
+
+    #[test]
+    fn wilson_interval_narrows_with_more_games() {
+        let (low, high) = wilson_interval(7, 10, 1.96);
+        let (more_low, more_high) = wilson_interval(700, 1000, 1.96);
+        assert!(low < more_low && more_high < high);
+        assert!(more_low < 0.7 && 0.7 < more_high);
+    }
+
+    #[test]
+    fn unit_records_count_hits_effects_and_deaths() {
+        let (wizard, warrior) = (UnitId::new(1), UnitId::new(2));
+        let report = DamageReport {
+            damage_type: DamageType::Physical,
+            raw: 6,
+            resisted: 1,
+            absorbed: 0,
+            dealt: 5,
+            overkill: 0,
+            remaining: 5,
+            killed: false,
+        };
+        let poison = StatusEffect::new(EffectKind::Poison, 2, 3);
+        let record = GameRecord {
+            seed: 0,
+            roster: vec![
+                RosterEntry { id: wizard, class: UnitClass::Wizard, side: Side::Player },
+                RosterEntry { id: warrior, class: UnitClass::Warrior, side: Side::Enemy },
+            ],
+            winner: Some(Side::Player),
+            turns: 4,
+            log: vec![
+                log(1, Side::Enemy, BattleEvent::UnitDamaged { attacker: warrior, target: wizard, report }),
+                log(1, Side::Enemy, BattleEvent::TurnEnded { side: Side::Enemy, next: Side::Player }),
+                log(2, Side::Player, BattleEvent::AbilityUsed { unit: wizard, ability: "poison", target: Tile::new(2, 1) }),
+                log(2, Side::Player, BattleEvent::EffectApplied { unit: warrior, effect: poison }),
+                log(2, Side::Player, BattleEvent::TurnEnded { side: Side::Player, next: Side::Enemy }),
+                log(2, Side::Player, BattleEvent::EffectTicked { unit: warrior, kind: EffectKind::Poison, change: -3, remaining: 2 }),
+                log(3, Side::Player, BattleEvent::EffectTicked { unit: warrior, kind: EffectKind::Poison, change: -2, remaining: 0 }),
+                log(3, Side::Player, BattleEvent::UnitDied { unit: warrior }),
+            ],
+        };
+        let records = unit_records(&record);
+        assert_eq!(records.len(), 2);
+        let (wizard, warrior) = (&records[0], &records[1]);
+        assert_eq!((wizard.damage_dealt, wizard.damage_taken, wizard.survived_turns, wizard.won), (5, 5, 4, true));
+        assert_eq!((warrior.damage_dealt, warrior.damage_taken, warrior.survived_turns, warrior.won), (5, 5, 3, false));
+    }
+
+    #[test]
+    fn effects_nobody_cast_count_only_as_taken() {
+        let warrior = UnitId::new(1);
+        let record = GameRecord {
+            seed: 0,
+            roster: vec![RosterEntry { id: warrior, class: UnitClass::Warrior, side: Side::Enemy }],
+            winner: None,
+            turns: 2,
+            log: vec![
+                log(1, Side::Player, BattleEvent::EffectApplied { unit: warrior, effect: StatusEffect::new(EffectKind::Poison, 1, 4) }),
+                log(1, Side::Player, BattleEvent::EffectTicked { unit: warrior, kind: EffectKind::Poison, change: -4, remaining: 6 }),
+            ],
+        };
+        let records = unit_records(&record);
+        assert_eq!((records[0].damage_dealt, records[0].damage_taken), (0, 4));
+    }
+}
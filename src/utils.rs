@@ -1,5 +1,7 @@
+use std::env;
 use std::error::Error;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug)]
 pub struct IndexError;
@@ -38,3 +40,23 @@ pub fn get_all<T>(data: &mut Vec<T>) -> Vec<&mut T> {
     }
     return_vec
 }
+
+/// Resource directories in the order ggez searches them.
+pub fn resource_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        dirs.push(Path::new(&manifest_dir).join("resources"));
+    }
+    if let Some(exe_dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        dirs.push(exe_dir.join("resources"));
+    }
+    dirs.push(PathBuf::from("resources"));
+    dirs
+}
+
+pub fn find_resource(relative: &Path) -> Option<PathBuf> {
+    resource_dirs()
+        .into_iter()
+        .map(|dir| dir.join(relative))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,70 @@
+/// The size of a map tile in art pixels, both on the sprite sheets and on
+/// the canvas.
+pub const PIXELS_PER_TILE: u32 = 8;
+/// How many tiles fit on the canvas across and down.
+pub const CANVAS_TILES: (u32, u32) = (40, 22);
+/// The size every scene is laid out and drawn at, in art pixels, before
+/// it's scaled up to fit the window.
+pub const CANVAS_SIZE: (u32, u32) = (CANVAS_TILES.0 * PIXELS_PER_TILE, CANVAS_TILES.1 * PIXELS_PER_TILE);
+
+/// Where the canvas goes in the window: scaled up by the largest whole
+/// number that fits, and centred with black bars around it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Viewport {
+    window: (u32, u32),
+    /// The most the canvas is scaled up, however big the window.
+    max_scale: u32,
+}
+
+impl Viewport {
+    pub fn new(window_w: u32, window_h: u32, max_scale: u32) -> Self {
+        Viewport {
+            window: (window_w, window_h),
+            max_scale,
+        }
+    }
+
+    pub fn set_max_scale(&mut self, max_scale: u32) {
+        self.max_scale = max_scale;
+    }
+
+    pub fn resize(&mut self, window_w: u32, window_h: u32) {
+        self.window = (window_w, window_h);
+    }
+
+    pub fn window(&self) -> (u32, u32) {
+        self.window
+    }
+
+    /// Never below one, so a window smaller than the canvas crops it
+    /// instead of blurring it.
+    pub fn scale(&self) -> u32 {
+        let scale_x = self.window.0 / CANVAS_SIZE.0;
+        let scale_y = self.window.1 / CANVAS_SIZE.1;
+        scale_x.min(scale_y).min(self.max_scale).max(1)
+    }
+
+    /// The window position of the canvas's top left corner.
+    pub fn offset(&self) -> (i32, i32) {
+        let scale = self.scale();
+        let x = (self.window.0 as i32 - (CANVAS_SIZE.0 * scale) as i32) / 2;
+        let y = (self.window.1 as i32 - (CANVAS_SIZE.1 * scale) as i32) / 2;
+        (x, y)
+    }
+
+    /// Turns a window position into a canvas one, or nothing if it falls on
+    /// the bars.
+    pub fn to_canvas(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let scale = self.scale() as i32;
+        let (offset_x, offset_y) = self.offset();
+        let (x, y) = (x - offset_x, y - offset_y);
+        if x < 0 || y < 0 {
+            return None;
+        }
+        let (x, y) = (x / scale, y / scale);
+        if x >= CANVAS_SIZE.0 as i32 || y >= CANVAS_SIZE.1 as i32 {
+            return None;
+        }
+        Some((x, y))
+    }
+}